@@ -0,0 +1,193 @@
+//! Line codec for [`tokio_util::codec`]
+//!
+//! Frames lines like [`CrlfLines`](crate::lines::CrlfLines), i.e., line endings are
+//! accepted as by [`streaming::crlf_with`](crate::streaming::crlf_with).
+
+use bytes::{BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{
+    is_cr, is_lf,
+    lines::{scan, LineError, Scan, DEFAULT_MAX_LEN},
+    NewlinePolicy,
+};
+
+/// Decodes lines without their line ending and encodes lines with a trailing CRLF.
+#[derive(Clone, Debug)]
+pub struct CrlfLinesCodec {
+    policy: NewlinePolicy,
+    max_len: usize,
+    scanned: usize,
+}
+
+impl CrlfLinesCodec {
+    /// Lines terminated by CRLF, at most [`DEFAULT_MAX_LEN`] bytes long.
+    pub fn new() -> Self {
+        Self {
+            policy: NewlinePolicy::Strict,
+            max_len: DEFAULT_MAX_LEN,
+            scanned: 0,
+        }
+    }
+
+    /// Set the line endings accepted by the decoder.
+    ///
+    /// The encoder always writes CRLF.
+    pub fn policy(mut self, policy: NewlinePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the maximum length of a line, excluding its line ending.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    fn decode_line(
+        &mut self,
+        buf: &mut BytesMut,
+        eof: bool,
+    ) -> Result<Option<BytesMut>, LineError> {
+        match scan(buf, self.scanned, self.policy, eof)? {
+            Scan::Line { end, next } => {
+                if end > self.max_len {
+                    return Err(LineError::TooLong);
+                }
+
+                let mut line = buf.split_to(next);
+                line.truncate(end);
+                self.scanned = 0;
+
+                Ok(Some(line))
+            }
+            Scan::Incomplete { resume } => {
+                if resume > self.max_len {
+                    return Err(LineError::TooLong);
+                }
+
+                self.scanned = resume;
+
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Default for CrlfLinesCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder for CrlfLinesCodec {
+    type Item = BytesMut;
+    type Error = LineError;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.decode_line(buf, false)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode_line(buf, true)? {
+            Some(line) => Ok(Some(line)),
+            None if buf.is_empty() => Ok(None),
+            None => Err(LineError::Truncated),
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> Encoder<T> for CrlfLinesCodec {
+    type Error = LineError;
+
+    fn encode(&mut self, line: T, buf: &mut BytesMut) -> Result<(), Self::Error> {
+        let line = line.as_ref();
+
+        if line.len() > self.max_len {
+            return Err(LineError::TooLong);
+        }
+
+        if let Some(&b) = line.iter().find(|&&b| is_cr(b) || is_lf(b)) {
+            return Err(if is_cr(b) {
+                LineError::BareCr
+            } else {
+                LineError::BareLf
+            });
+        }
+
+        buf.reserve(line.len() + 2);
+        buf.put_slice(line);
+        buf.put_slice(b"\r\n");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::{duplex, AsyncWriteExt};
+    use tokio_util::codec::{FramedRead, FramedWrite};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_decode() {
+        let (mut client, server) = duplex(4);
+        let mut lines = FramedRead::new(server, CrlfLinesCodec::new().max_len(8));
+
+        tokio::spawn(async move {
+            client.write_all(b"EHLO x\r\n\r\nNOOP\r").await.unwrap();
+            client.write_all(b"\nQUIT\nx").await.unwrap();
+        });
+
+        assert_eq!(lines.next().await.unwrap().unwrap(), &b"EHLO x"[..]);
+        assert_eq!(lines.next().await.unwrap().unwrap(), &b""[..]);
+        assert_eq!(lines.next().await.unwrap().unwrap(), &b"NOOP"[..]);
+        assert!(matches!(
+            lines.next().await.unwrap(),
+            Err(LineError::BareLf)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_decode_errors() {
+        let mut codec = CrlfLinesCodec::new().max_len(4);
+
+        assert!(matches!(
+            codec.decode(&mut BytesMut::from(&b"abcde"[..])),
+            Err(LineError::TooLong)
+        ));
+        assert!(matches!(
+            codec.decode(&mut BytesMut::from(&b"a\rb"[..])),
+            Err(LineError::BareCr)
+        ));
+        assert!(matches!(
+            CrlfLinesCodec::new().decode_eof(&mut BytesMut::from(&b"abc"[..])),
+            Err(LineError::Truncated)
+        ));
+        assert_eq!(
+            CrlfLinesCodec::new()
+                .policy(NewlinePolicy::AllowLf)
+                .decode(&mut BytesMut::from(&b"abc\n"[..]))
+                .unwrap()
+                .unwrap(),
+            &b"abc"[..]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_encode() {
+        let mut buf = Vec::new();
+        let mut lines = FramedWrite::new(&mut buf, CrlfLinesCodec::new());
+
+        lines.send("250 OK").await.unwrap();
+        assert!(matches!(
+            lines.send("250\nOK").await,
+            Err(LineError::BareLf)
+        ));
+        lines.send("").await.unwrap();
+
+        assert_eq!(buf, b"250 OK\r\n\r\n");
+    }
+}
@@ -0,0 +1,94 @@
+//! Rendering of bytes in ABNF notation
+//!
+//! [`AbnfEscaped`] prints VCHAR and SP as-is and every other byte as `%xHH`,
+//! which makes untrusted input safe to log. `%` is escaped as `%x25`, so that the
+//! output is unambiguous.
+//!
+//! ```
+//! use abnf_core::escape::AbnfEscaped;
+//!
+//! assert_eq!(AbnfEscaped(b"a\tb\r\n").to_string(), "a%x09b%x0D%x0A");
+//! assert_eq!(format!("{:#}", AbnfEscaped(b"a\tb\r\n")), "a%x09b%x0D.0A");
+//! assert_eq!(AbnfEscaped(b"%x0D").to_string(), "%x25x0D");
+//! ```
+
+use core::fmt::{Display, Formatter, Write};
+
+use crate::{is_sp, is_vchar};
+
+/// Display adapter which renders bytes in ABNF notation.
+///
+/// VCHAR and SP are printed as-is, except for `%`. All other bytes are printed as `%xHH`.
+///
+/// The alternate flag (`{:#}`) groups consecutive escaped bytes, e.g., `%x0D.0A`
+/// instead of `%x0D%x0A`. A `.` directly after an escaped byte is escaped then, too.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AbnfEscaped<'a>(pub &'a [u8]);
+
+impl Display for AbnfEscaped<'_> {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        let group = f.alternate();
+        let mut escaped = false;
+
+        for &b in self.0 {
+            // An unescaped `%` could not be told apart from an escape sequence, nor could
+            // an unescaped `.` after an escape when grouping.
+            let literal =
+                (is_vchar(b) || is_sp(b)) && b != b'%' && !(group && escaped && b == b'.');
+
+            if literal {
+                f.write_char(b as char)?;
+                escaped = false;
+            } else {
+                if group && escaped {
+                    write!(f, ".{b:02X}")?;
+                } else {
+                    write!(f, "%x{b:02X}")?;
+                }
+                escaped = true;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use alloc::{format, string::ToString};
+
+    use super::*;
+
+    #[test]
+    fn test_abnf_escaped() {
+        let tests: &[(&[u8], &str, &str)] = &[
+            (b"", "", ""),
+            (b"Subject: x", "Subject: x", "Subject: x"),
+            (b"\r\n", "%x0D%x0A", "%x0D.0A"),
+            (b"\x00a\x7F\xFF", "%x00a%x7F%xFF", "%x00a%x7F.FF"),
+            (b" \t ", " %x09 ", " %x09 "),
+            (b"%x0D", "%x25x0D", "%x25x0D"),
+            (b"\r%", "%x0D%x25", "%x0D.25"),
+            (b"\r.0A", "%x0D.0A", "%x0D.2E0A"),
+            (b"a.b", "a.b", "a.b"),
+        ];
+
+        for (input, plain, grouped) in tests {
+            assert_eq!(AbnfEscaped(input).to_string(), *plain);
+            assert_eq!(format!("{:#}", AbnfEscaped(input)), *grouped);
+        }
+    }
+
+    #[test]
+    fn test_abnf_escaped_all() {
+        for b in 0..=255u8 {
+            let out = AbnfEscaped(&[b]).to_string();
+
+            if (is_vchar(b) || is_sp(b)) && b != b'%' {
+                assert_eq!(out.as_bytes(), [b]);
+            } else {
+                assert_eq!(out, format!("%x{b:02X}"));
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Unfolding and folding of header lines
+//!
+//! A header line is folded by inserting CRLF before WSP and unfolded by removing every
+//! CRLF which is immediately followed by WSP (see RFC5322, 2.2.3).
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use nom::Err as OutCome;
+
+use crate::{is_cr, is_wsp, streaming::crlf};
+
+/// Remove every CRLF which is followed by WSP.
+///
+/// A CRLF at the end of `input` is kept, as there is nothing to continue it.
+/// Use [`unfold_partial`] if `input` is an incomplete buffer.
+pub fn unfold(input: &[u8]) -> Cow<'_, [u8]> {
+    unfold_impl(input, false).0
+}
+
+/// Remove every CRLF which is followed by WSP from a buffer which may be incomplete.
+///
+/// Returns the unfolded data and the number of consumed bytes. A trailing CR or CRLF
+/// is not consumed, because it can only be unfolded once the next byte is known.
+pub fn unfold_partial(input: &[u8]) -> (Cow<'_, [u8]>, usize) {
+    unfold_impl(input, true)
+}
+
+fn unfold_impl(input: &[u8], partial: bool) -> (Cow<'_, [u8]>, usize) {
+    let mut unfolded: Option<Vec<u8>> = None;
+    let mut copied = 0;
+    let mut end = input.len();
+    let mut i = 0;
+
+    while i < input.len() {
+        if is_cr(input[i]) {
+            match crlf::<_, ()>(&input[i..]) {
+                Ok((rest, _)) => match rest.first() {
+                    Some(&b) if is_wsp(b) => {
+                        unfolded
+                            .get_or_insert_with(Vec::new)
+                            .extend_from_slice(&input[copied..i]);
+                        i += 2;
+                        copied = i;
+                        continue;
+                    }
+                    None if partial => {
+                        end = i;
+                        break;
+                    }
+                    _ => {}
+                },
+                Err(OutCome::Incomplete(_)) if partial => {
+                    end = i;
+                    break;
+                }
+                Err(_) => {}
+            }
+        }
+
+        i += 1;
+    }
+
+    match unfolded {
+        Some(mut unfolded) => {
+            unfolded.extend_from_slice(&input[copied..end]);
+            (Cow::Owned(unfolded), end)
+        }
+        None => (Cow::Borrowed(&input[..end]), end),
+    }
+}
+
+/// Insert CRLF before WSP, so that no line is longer than `max_len` bytes (excluding CRLF).
+///
+/// A fold is only inserted where it does not produce a line which only contains white
+/// space. Lines without such a point are left longer than `max_len`. CRLFs which are
+/// already in `line` start a new line.
+pub fn fold(line: &[u8], max_len: usize) -> Cow<'_, [u8]> {
+    let mut folded: Option<Vec<u8>> = None;
+    let mut copied = 0;
+    let mut line_start = 0;
+    let mut has_content = false;
+    let mut content_end = last_content(line, 0);
+    let mut last_break = None;
+    let mut i = 0;
+
+    while i < line.len() {
+        if crlf::<_, ()>(&line[i..]).is_ok() {
+            i += 2;
+            line_start = i;
+            has_content = false;
+            content_end = last_content(line, i);
+            last_break = None;
+            continue;
+        }
+
+        if is_wsp(line[i]) {
+            // Folding at trailing WSP would leave a whitespace-only continuation line.
+            if has_content && content_end.is_some_and(|last| i < last) {
+                last_break = Some(i);
+            }
+        } else {
+            has_content = true;
+        }
+
+        if i + 1 - line_start > max_len {
+            if let Some(at) = last_break.take() {
+                let folded = folded.get_or_insert_with(Vec::new);
+                folded.extend_from_slice(&line[copied..at]);
+                folded.extend_from_slice(b"\r\n");
+                copied = at;
+                line_start = at;
+                has_content = line[at..=i].iter().any(|&b| !is_wsp(b));
+            }
+        }
+
+        i += 1;
+    }
+
+    match folded {
+        Some(mut folded) => {
+            folded.extend_from_slice(&line[copied..]);
+            Cow::Owned(folded)
+        }
+        None => Cow::Borrowed(line),
+    }
+}
+
+/// Offset of the last non-WSP byte between `from` and the next CRLF (or the end).
+fn last_content(line: &[u8], from: usize) -> Option<usize> {
+    let mut last = None;
+    let mut i = from;
+
+    while i < line.len() && crlf::<_, ()>(&line[i..]).is_err() {
+        if !is_wsp(line[i]) {
+            last = Some(i);
+        }
+        i += 1;
+    }
+
+    last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unfold() {
+        assert!(matches!(
+            unfold(b"Subject: x"),
+            Cow::Borrowed(b"Subject: x")
+        ));
+        assert_eq!(unfold(b"Subject: a\r\n b\r\n\tc"), &b"Subject: a b\tc"[..]);
+        assert_eq!(unfold(b"a\r\nb\r\n"), &b"a\r\nb\r\n"[..]);
+        assert_eq!(unfold(b"a\r\n \r\n b"), &b"a  b"[..]);
+        assert_eq!(unfold(b"a\n b\r"), &b"a\n b\r"[..]);
+    }
+
+    #[test]
+    fn test_unfold_partial() {
+        assert_eq!(unfold_partial(b"a\r\n b"), (Cow::Borrowed(&b"a b"[..]), 5));
+        assert_eq!(
+            unfold_partial(b"a\r\n b\r\n"),
+            (Cow::Borrowed(&b"a b"[..]), 5)
+        );
+        assert_eq!(
+            unfold_partial(b"a\r\n b\r"),
+            (Cow::Borrowed(&b"a b"[..]), 5)
+        );
+        assert_eq!(
+            unfold_partial(b"a\r\nb\r"),
+            (Cow::Borrowed(&b"a\r\nb"[..]), 4)
+        );
+    }
+
+    #[test]
+    fn test_fold() {
+        assert!(matches!(
+            fold(b"Subject: x", 78),
+            Cow::Borrowed(b"Subject: x")
+        ));
+        assert_eq!(
+            fold(b"Subject: aaa bbb ccc", 12),
+            &b"Subject: aaa\r\n bbb ccc"[..]
+        );
+        assert_eq!(fold(b"a b c d", 1), &b"a\r\n b\r\n c\r\n d"[..]);
+        assert_eq!(fold(b"   aaaa", 2), &b"   aaaa"[..]);
+        assert_eq!(fold(b"aa\r\nbb cc", 4), &b"aa\r\nbb\r\n cc"[..]);
+        assert_eq!(fold(b"aa   ", 2), &b"aa   "[..]);
+        assert_eq!(fold(b"Subject: a   ", 10), &b"Subject:\r\n a   "[..]);
+        assert_eq!(fold(b"a b  \r\nc", 1), &b"a\r\n b  \r\nc"[..]);
+
+        for line in [
+            &b"Subject: aaa bbb ccc"[..],
+            b"a  b\tc d",
+            b"x\r\n yy zz",
+            b"Subject: a   ",
+        ] {
+            for max_len in 1..10 {
+                assert_eq!(unfold(&fold(line, max_len)), unfold(line));
+            }
+        }
+    }
+}
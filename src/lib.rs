@@ -3,12 +3,121 @@
 //!
 //! See <https://tools.ietf.org/html/rfc5234#appendix-B.1>
 //!
+//! The crate is `no_std` compatible. The `std` feature is enabled by default.
+//! All predicates and parsers are available without allocation, APIs which
+//! allocate are gated behind the `alloc` feature.
+//!
+//! HEXDIG and LWSP are ambiguous in RFC5234. [`complete::strict`] and [`complete::lenient`]
+//! (and their [`streaming`] counterparts) contain all core rules in one reading each,
+//! so a grammar parser selects a profile by importing core rules from one of them.
+//! Tooling which selects a profile at runtime passes a [`Profile`] to
+//! [`CoreRule::complete_with`] or [`CoreRule::streaming_with`].
+//!
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// The predicates intentionally mirror the `%x` ranges of RFC5234.
+#![allow(clippy::manual_is_ascii_check)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 
+#[cfg(feature = "tokio")]
+pub mod codec;
 pub mod complete;
+pub mod escape;
+#[cfg(feature = "alloc")]
+pub mod folding;
+#[cfg(feature = "std")]
+pub mod lines;
+#[cfg(feature = "nom_locate")]
+pub mod located;
+#[cfg(feature = "nom8")]
+pub mod nom8;
+pub mod rule;
+pub mod simd;
+#[cfg(feature = "alloc")]
+pub mod stream;
 pub mod streaming;
+pub mod validate;
+#[cfg(feature = "winnow")]
+pub mod winnow;
 
 use nom::AsChar;
 
+pub use crate::rule::{classify, CoreRule, CoreRuleSet};
+
+/// Character classes of the single-character core rules
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CharClass {
+    Alpha,
+    Bit,
+    Char,
+    Cr,
+    Ctl,
+    Digit,
+    Dquote,
+    Hexdig,
+    Htab,
+    Lf,
+    Octet,
+    Sp,
+    Vchar,
+    Wsp,
+}
+
+impl CharClass {
+    /// Whether `b` belongs to this class.
+    pub fn contains(self, b: u8) -> bool {
+        match self {
+            CharClass::Alpha => is_alpha(b),
+            CharClass::Bit => is_bit(b),
+            CharClass::Char => is_char(b),
+            CharClass::Cr => is_cr(b),
+            CharClass::Ctl => is_ctl(b),
+            CharClass::Digit => is_digit(b),
+            CharClass::Dquote => is_dquote(b),
+            CharClass::Hexdig => is_hexdig(b),
+            CharClass::Htab => is_htab(b),
+            CharClass::Lf => is_lf(b),
+            CharClass::Octet => is_octet(b),
+            CharClass::Sp => is_sp(b),
+            CharClass::Vchar => is_vchar(b),
+            CharClass::Wsp => is_wsp(b),
+        }
+    }
+
+    /// The class as a union of ranges `lo..=hi`.
+    pub fn ranges(self) -> &'static [(u8, u8)] {
+        match self {
+            CharClass::Alpha => &[(0x41, 0x5A), (0x61, 0x7A)],
+            CharClass::Bit => &[(0x30, 0x31)],
+            CharClass::Char => &[(0x01, 0x7F)],
+            CharClass::Cr => &[(0x0D, 0x0D)],
+            CharClass::Ctl => &[(0x00, 0x1F), (0x7F, 0x7F)],
+            CharClass::Digit => &[(0x30, 0x39)],
+            CharClass::Dquote => &[(0x22, 0x22)],
+            CharClass::Hexdig => &[(0x30, 0x39), (0x41, 0x46), (0x61, 0x66)],
+            CharClass::Htab => &[(0x09, 0x09)],
+            CharClass::Lf => &[(0x0A, 0x0A)],
+            CharClass::Octet => &[(0x00, 0xFF)],
+            CharClass::Sp => &[(0x20, 0x20)],
+            CharClass::Vchar => &[(0x21, 0x7E)],
+            CharClass::Wsp => &[(0x09, 0x09), (0x20, 0x20)],
+        }
+    }
+
+    /// The class as a single range `lo..=hi`, if possible.
+    pub fn range(self) -> Option<(u8, u8)> {
+        match self.ranges() {
+            [range] => Some(*range),
+            _ => None,
+        }
+    }
+}
+
 /// A-Z / a-z
 ///
 /// ALPHA = %x41-5A / %x61-7A
@@ -39,6 +148,46 @@ pub fn is_cr(c: impl AsChar) -> bool {
 // CRLF
 // Not implemented as predicate.
 
+/// Line endings accepted by `crlf_with`, `lwsp_with` and other line-oriented rules.
+///
+/// Only [`NewlinePolicy::Strict`] follows CRLF = CR LF. The other policies are meant for
+/// servers which need to interoperate with peers that send bare line endings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NewlinePolicy {
+    /// "\r\n"
+    #[default]
+    Strict,
+    /// "\r\n" or "\n"
+    AllowLf,
+    /// "\r\n" or "\r" (old Mac)
+    AllowCr,
+    /// "\r\n", "\n" or "\r"
+    Any,
+}
+
+/// Reading of core rules which are ambiguous in RFC5234.
+///
+/// Each profile is also available as a module in [`complete`] and [`streaming`], e.g.,
+/// `complete::strict` and `complete::lenient`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// HEXDIG is uppercase only and LWSP does not span whitespace-only lines.
+    Strict,
+    /// HEXDIG is case-insensitive and LWSP is implemented as written in RFC5234.
+    #[default]
+    Lenient,
+}
+
+impl Profile {
+    /// HEXDIG according to this profile.
+    pub fn is_hexdig(self, c: impl AsChar) -> bool {
+        match self {
+            Profile::Strict => is_hexdig_strict(c),
+            Profile::Lenient => is_hexdig(c),
+        }
+    }
+}
+
 /// Controls
 ///
 /// CTL = %x00-1F / %x7F
@@ -68,6 +217,13 @@ pub fn is_hexdig(c: impl AsChar) -> bool {
     matches!(c.as_char(), '0'..='9' | 'a'..='f' | 'A'..='F')
 }
 
+/// HEXDIG = DIGIT / %x41-46
+///
+/// Strict variant of [`is_hexdig`] which only allows uppercase `A` / ... / `F`.
+pub fn is_hexdig_strict(c: impl AsChar) -> bool {
+    matches!(c.as_char(), '\x30'..='\x39' | '\x41'..='\x46')
+}
+
 /// Horizontal tab
 ///
 /// HTAB = %x09
@@ -85,6 +241,34 @@ pub fn is_lf(c: impl AsChar) -> bool {
 // LWSP
 // Not implemented as predicate.
 
+/// LWSP together with its folds, as returned by `lwsp_detailed`.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lwsp<I> {
+    /// Recognized input
+    pub span: I,
+    /// Every `CRLF WSP` in `span`
+    pub folds: Vec<Fold>,
+}
+
+#[cfg(feature = "alloc")]
+impl<I> Lwsp<I> {
+    /// Whether LWSP produced a line which only contains white space.
+    pub fn has_whitespace_only_lines(&self) -> bool {
+        self.folds.iter().any(|fold| fold.whitespace_only)
+    }
+}
+
+/// A `CRLF WSP` in LWSP.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Fold {
+    /// Offset of the CRLF, relative to the start of LWSP
+    pub offset: usize,
+    /// Whether the continuation line started by this fold only contains white space,
+    /// i.e., its white space is followed by CRLF or the end of input
+    pub whitespace_only: bool,
+}
+
 /// 8 bits of data
 ///
 /// OCTET = %x00-FF
@@ -117,6 +301,34 @@ pub fn is_wsp(c: impl AsChar) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_char_class() {
+        for class in [
+            CharClass::Alpha,
+            CharClass::Bit,
+            CharClass::Char,
+            CharClass::Cr,
+            CharClass::Ctl,
+            CharClass::Digit,
+            CharClass::Dquote,
+            CharClass::Hexdig,
+            CharClass::Htab,
+            CharClass::Lf,
+            CharClass::Octet,
+            CharClass::Sp,
+            CharClass::Vchar,
+            CharClass::Wsp,
+        ] {
+            for b in 0..=255 {
+                let in_ranges = class
+                    .ranges()
+                    .iter()
+                    .any(|(lo, hi)| (lo..=hi).contains(&&b));
+                assert_eq!(class.contains(b), in_ranges, "{class:?} {b}");
+            }
+        }
+    }
+
     #[test]
     fn test_is_alpha() {
         assert!(is_alpha(b'a'));
@@ -203,6 +415,20 @@ mod tests {
         assert!(!is_hexdig('Z'));
     }
 
+    #[test]
+    fn test_is_hexdig_strict() {
+        assert!(is_hexdig_strict(b'0'));
+        assert!(is_hexdig_strict('9'));
+        assert!(is_hexdig_strict('A'));
+        assert!(is_hexdig_strict('F'));
+        assert!(!is_hexdig_strict('a'));
+        assert!(!is_hexdig_strict('f'));
+        assert!(!is_hexdig_strict('G'));
+
+        assert!(Profile::Lenient.is_hexdig('a'));
+        assert!(!Profile::Strict.is_hexdig('a'));
+    }
+
     #[test]
     fn test_is_htab() {
         assert!(is_htab(b'\t'));
@@ -0,0 +1,467 @@
+//! Line framing over [`BufRead`]
+//!
+//! Lines are terminated as accepted by [`streaming::crlf_with`], so the framing matches
+//! what the core rules accept.
+
+use std::{
+    error, fmt,
+    io::{self, BufRead},
+};
+#[cfg(feature = "futures")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use nom::Err as OutCome;
+
+use crate::{complete, is_cr, is_lf, is_wsp, streaming, NewlinePolicy};
+
+/// Default for [`CrlfLines::max_len`]
+pub const DEFAULT_MAX_LEN: usize = 8192;
+
+/// Error while framing lines.
+#[derive(Debug)]
+pub enum LineError {
+    /// The underlying reader failed.
+    Io(io::Error),
+    /// A line is longer than the configured maximum.
+    TooLong,
+    /// A CR which does not terminate a line according to the newline policy.
+    BareCr,
+    /// A LF which does not terminate a line according to the newline policy.
+    BareLf,
+    /// The input ended within a line.
+    Truncated,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineError::Io(error) => write!(f, "{error}"),
+            LineError::TooLong => write!(f, "line too long"),
+            LineError::BareCr => write!(f, "bare CR"),
+            LineError::BareLf => write!(f, "bare LF"),
+            LineError::Truncated => write!(f, "input ended within a line"),
+        }
+    }
+}
+
+impl error::Error for LineError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            LineError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LineError {
+    fn from(error: io::Error) -> Self {
+        LineError::Io(error)
+    }
+}
+
+pub(crate) enum Scan {
+    /// Line content ends at `end` and the next line starts at `next`.
+    Line { end: usize, next: usize },
+    /// No line ending was found. Scanning can resume at `resume`.
+    Incomplete { resume: usize },
+}
+
+/// Find the first line ending in `buf[from..]`.
+///
+/// When `eof` is true, `buf` is known to be complete.
+pub(crate) fn scan(
+    buf: &[u8],
+    from: usize,
+    policy: NewlinePolicy,
+    eof: bool,
+) -> Result<Scan, LineError> {
+    for (i, &b) in buf.iter().enumerate().skip(from) {
+        if !is_cr(b) && !is_lf(b) {
+            continue;
+        }
+
+        let newline = if eof {
+            complete::crlf_with::<_, ()>(policy)(&buf[i..])
+        } else {
+            streaming::crlf_with::<_, ()>(policy)(&buf[i..])
+        };
+
+        return match newline {
+            Ok((_, newline)) => Ok(Scan::Line {
+                end: i,
+                next: i + newline.len(),
+            }),
+            Err(OutCome::Incomplete(_)) => Ok(Scan::Incomplete { resume: i }),
+            Err(_) if is_cr(b) => Err(LineError::BareCr),
+            Err(_) => Err(LineError::BareLf),
+        };
+    }
+
+    Ok(Scan::Incomplete { resume: buf.len() })
+}
+
+/// Line buffer shared by the sync and async line readers.
+pub(crate) struct Framer {
+    pub(crate) policy: NewlinePolicy,
+    pub(crate) max_len: usize,
+    pub(crate) unfold: bool,
+    buf: Vec<u8>,
+    scanned: usize,
+    eof: bool,
+}
+
+pub(crate) enum Step {
+    Line(Vec<u8>),
+    NeedMore,
+    End,
+}
+
+impl Framer {
+    pub(crate) fn new() -> Self {
+        Self {
+            policy: NewlinePolicy::Strict,
+            max_len: DEFAULT_MAX_LEN,
+            unfold: false,
+            buf: Vec::new(),
+            scanned: 0,
+            eof: false,
+        }
+    }
+
+    /// Append data read from the source. An empty chunk marks the end of input.
+    pub(crate) fn push(&mut self, chunk: &[u8]) {
+        if chunk.is_empty() {
+            self.eof = true;
+        } else {
+            self.buf.extend_from_slice(chunk);
+        }
+    }
+
+    pub(crate) fn step(&mut self) -> Result<Step, LineError> {
+        loop {
+            match scan(&self.buf, self.scanned, self.policy, self.eof)? {
+                Scan::Line { end, next } => {
+                    if end > self.max_len {
+                        return Err(LineError::TooLong);
+                    }
+
+                    if self.unfold {
+                        match self.buf.get(next) {
+                            Some(&b) if is_wsp(b) => {
+                                self.buf.drain(end..next);
+                                self.scanned = end;
+                                continue;
+                            }
+                            None if !self.eof => {
+                                self.scanned = end;
+                                return Ok(Step::NeedMore);
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    let line = self.buf[..end].to_vec();
+                    self.buf.drain(..next);
+                    self.scanned = 0;
+
+                    return Ok(Step::Line(line));
+                }
+                Scan::Incomplete { resume } => {
+                    if resume > self.max_len {
+                        return Err(LineError::TooLong);
+                    }
+
+                    if self.eof {
+                        return if self.buf.is_empty() {
+                            Ok(Step::End)
+                        } else {
+                            Err(LineError::Truncated)
+                        };
+                    }
+
+                    self.scanned = resume;
+                    return Ok(Step::NeedMore);
+                }
+            }
+        }
+    }
+}
+
+/// Iterator over the lines of a [`BufRead`].
+///
+/// Lines are yielded without their line ending. After an error, the iterator is exhausted.
+pub struct CrlfLines<R> {
+    reader: R,
+    framer: Framer,
+    done: bool,
+}
+
+impl<R: BufRead> CrlfLines<R> {
+    /// Lines terminated by CRLF, at most [`DEFAULT_MAX_LEN`] bytes long.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            framer: Framer::new(),
+            done: false,
+        }
+    }
+
+    /// Set the accepted line endings.
+    pub fn policy(mut self, policy: NewlinePolicy) -> Self {
+        self.framer.policy = policy;
+        self
+    }
+
+    /// Set the maximum length of a line, excluding its line ending.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.framer.max_len = max_len;
+        self
+    }
+
+    /// Join lines which start with WSP to the previous line (LWSP continuation).
+    ///
+    /// Only the line ending is removed, the WSP is kept.
+    pub fn unfold(mut self, unfold: bool) -> Self {
+        self.framer.unfold = unfold;
+        self
+    }
+
+    /// Unwrap the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn next_line(&mut self) -> Result<Option<Vec<u8>>, LineError> {
+        loop {
+            match self.framer.step()? {
+                Step::Line(line) => return Ok(Some(line)),
+                Step::End => return Ok(None),
+                Step::NeedMore => {
+                    let chunk = self.reader.fill_buf()?;
+                    let len = chunk.len();
+                    self.framer.push(chunk);
+                    self.reader.consume(len);
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for CrlfLines<R> {
+    type Item = Result<Vec<u8>, LineError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_line() {
+            Ok(Some(line)) => Some(Ok(line)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Stream over the lines of an [`AsyncBufRead`](futures_io::AsyncBufRead).
+///
+/// This is the async counterpart of [`CrlfLines`] and is configured the same way.
+#[cfg(feature = "futures")]
+pub struct AsyncCrlfLines<R> {
+    reader: R,
+    framer: Framer,
+    done: bool,
+}
+
+#[cfg(feature = "futures")]
+impl<R: futures_io::AsyncBufRead + Unpin> AsyncCrlfLines<R> {
+    /// Lines terminated by CRLF, at most [`DEFAULT_MAX_LEN`] bytes long.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            framer: Framer::new(),
+            done: false,
+        }
+    }
+
+    /// Set the accepted line endings.
+    pub fn policy(mut self, policy: NewlinePolicy) -> Self {
+        self.framer.policy = policy;
+        self
+    }
+
+    /// Set the maximum length of a line, excluding its line ending.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.framer.max_len = max_len;
+        self
+    }
+
+    /// Join lines which start with WSP to the previous line (LWSP continuation).
+    ///
+    /// Only the line ending is removed, the WSP is kept.
+    pub fn unfold(mut self, unfold: bool) -> Self {
+        self.framer.unfold = unfold;
+        self
+    }
+
+    /// Unwrap the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn poll_line(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<Vec<u8>>, LineError>> {
+        loop {
+            match self.framer.step()? {
+                Step::Line(line) => return Poll::Ready(Ok(Some(line))),
+                Step::End => return Poll::Ready(Ok(None)),
+                Step::NeedMore => {
+                    let chunk = match Pin::new(&mut self.reader).poll_fill_buf(cx) {
+                        Poll::Ready(chunk) => chunk?,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    let len = chunk.len();
+                    self.framer.push(chunk);
+                    Pin::new(&mut self.reader).consume(len);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<R: futures_io::AsyncBufRead + Unpin> futures_core::Stream for AsyncCrlfLines<R> {
+    type Item = Result<Vec<u8>, LineError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        match this.poll_line(cx) {
+            Poll::Ready(Ok(Some(line))) => Poll::Ready(Some(Ok(line))),
+            Poll::Ready(Ok(None)) => {
+                this.done = true;
+                Poll::Ready(None)
+            }
+            Poll::Ready(Err(error)) => {
+                this.done = true;
+                Poll::Ready(Some(Err(error)))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+
+    fn lines(
+        input: &[u8],
+        configure: impl Fn(CrlfLines<BufReader<&[u8]>>) -> CrlfLines<BufReader<&[u8]>>,
+    ) -> Vec<Result<Vec<u8>, String>> {
+        // Read byte by byte to hit every chunk boundary.
+        configure(CrlfLines::new(BufReader::with_capacity(1, input)))
+            .map(|line| line.map_err(|error| error.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_strict() {
+        assert_eq!(lines(b"", |l| l), []);
+        assert_eq!(
+            lines(b"a\r\n\r\nbc\r\n", |l| l),
+            [Ok(b"a".to_vec()), Ok(b"".to_vec()), Ok(b"bc".to_vec())]
+        );
+        assert_eq!(
+            lines(b"a\r\nb\nc\r\n", |l| l),
+            [Ok(b"a".to_vec()), Err("bare LF".into())]
+        );
+        assert_eq!(lines(b"a\rb\r\n", |l| l), [Err("bare CR".into())]);
+        assert_eq!(
+            lines(b"a\r\nb", |l| l),
+            [Ok(b"a".to_vec()), Err("input ended within a line".into())]
+        );
+        assert_eq!(
+            lines(b"a\r\nb\r", |l| l),
+            [Ok(b"a".to_vec()), Err("bare CR".into())]
+        );
+    }
+
+    #[test]
+    fn test_policy() {
+        assert_eq!(
+            lines(b"a\nb\r\n", |l| l.policy(NewlinePolicy::AllowLf)),
+            [Ok(b"a".to_vec()), Ok(b"b".to_vec())]
+        );
+        assert_eq!(
+            lines(b"a\rb\r\nc\r", |l| l.policy(NewlinePolicy::AllowCr)),
+            [Ok(b"a".to_vec()), Ok(b"b".to_vec()), Ok(b"c".to_vec())]
+        );
+        assert_eq!(
+            lines(b"a\rb\r\nc\n", |l| l.policy(NewlinePolicy::AllowLf)),
+            [Err("bare CR".into())]
+        );
+    }
+
+    #[test]
+    fn test_max_len() {
+        assert_eq!(
+            lines(b"abc\r\nabcd\r\n", |l| l.max_len(3)),
+            [Ok(b"abc".to_vec()), Err("line too long".into())]
+        );
+        assert_eq!(
+            lines(b"abcdefgh", |l| l.max_len(3)),
+            [Err("line too long".into())]
+        );
+    }
+
+    #[test]
+    fn test_unfold() {
+        assert_eq!(
+            lines(b"a\r\n b\r\n\tc\r\nd\r\n ", |l| l.unfold(true)),
+            [
+                Ok(b"a b\tc".to_vec()),
+                Err("input ended within a line".into())
+            ]
+        );
+        assert_eq!(
+            lines(b"a\r\n b\r\nc\r\n", |l| l.unfold(true)),
+            [Ok(b"a b".to_vec()), Ok(b"c".to_vec())]
+        );
+        assert_eq!(
+            lines(b"a\r\n b\r\n", |l| l.unfold(true).max_len(2)),
+            [Err("line too long".into())]
+        );
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn test_async() {
+        use futures::{executor::block_on, io::BufReader, StreamExt};
+
+        let input = &b"a\r\n b\r\nc\nd\r\n"[..];
+        let lines: Vec<_> = block_on(
+            AsyncCrlfLines::new(BufReader::with_capacity(1, input))
+                .unfold(true)
+                .collect(),
+        );
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].as_ref().unwrap(), b"a b");
+        assert!(matches!(lines[1], Err(LineError::BareLf)));
+    }
+}
@@ -0,0 +1,173 @@
+//! Location tracking with [`nom_locate`]
+//!
+//! All parsers in [`crate::complete`] and [`crate::streaming`] accept [`LocatedSpan`] inputs.
+//! This module adds what is needed to report where a rule matched or failed.
+
+use core::{fmt, ops::RangeTo};
+
+use nom::{
+    combinator::{consumed, map},
+    error::{ErrorKind, ParseError},
+    AsBytes, IResult, Offset, Parser, Slice,
+};
+use nom_locate::LocatedSpan;
+
+/// Input type for location-tracking parsers.
+pub type Span<'a> = LocatedSpan<&'a [u8]>;
+
+/// Position of a span in the original input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Location {
+    /// Byte offset, starting at 0
+    pub offset: usize,
+    /// Line, starting at 1
+    pub line: u32,
+    /// Column in bytes, starting at 1
+    pub column: usize,
+}
+
+impl<T: AsBytes, X> From<&LocatedSpan<T, X>> for Location {
+    fn from(span: &LocatedSpan<T, X>) -> Self {
+        Self {
+            offset: span.location_offset(),
+            line: span.location_line(),
+            column: span.get_column(),
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {} (offset {})",
+            self.line, self.column, self.offset
+        )
+    }
+}
+
+/// Parse error which remembers where it happened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Error {
+    pub location: Location,
+    pub kind: ErrorKind,
+}
+
+impl<T: AsBytes, X> ParseError<LocatedSpan<T, X>> for Error {
+    fn from_error_kind(input: LocatedSpan<T, X>, kind: ErrorKind) -> Self {
+        Self {
+            location: Location::from(&input),
+            kind,
+        }
+    }
+
+    fn append(_: LocatedSpan<T, X>, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.kind.description(), self.location)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Output of a parser together with the input it consumed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Spanned<I, O> {
+    pub span: I,
+    pub value: O,
+}
+
+impl<T: AsBytes, X, O> Spanned<LocatedSpan<T, X>, O> {
+    /// Location of the first consumed byte.
+    pub fn location(&self) -> Location {
+        Location::from(&self.span)
+    }
+}
+
+/// Wrap a parser so that it also returns the consumed input.
+pub fn spanned<I, O, E, F>(parser: F) -> impl FnMut(I) -> IResult<I, Spanned<I, O>, E>
+where
+    I: Clone + Offset + Slice<RangeTo<usize>>,
+    E: ParseError<I>,
+    F: Parser<I, O, E>,
+{
+    map(consumed(parser), |(span, value)| Spanned { span, value })
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::{multi::many1_count, sequence::preceded};
+
+    use super::*;
+    use crate::complete::*;
+
+    #[test]
+    fn test_parsers() {
+        let input = Span::new(b"aZ09F\"\t\r\n \x01\x7f~\x00 \r\n x");
+
+        let (rem, _) = alpha::<_, Error>(input).unwrap();
+        let (rem, _) = alpha::<_, Error>(rem).unwrap();
+        let (rem, _) = bit::<_, Error>(rem).unwrap();
+        let (rem, _) = digit::<_, Error>(rem).unwrap();
+        let (rem, _) = hexdig::<_, Error>(rem).unwrap();
+        let (rem, _) = dquote::<_, Error>(rem).unwrap();
+        let (rem, _) = htab::<_, Error>(rem).unwrap();
+        let (rem, _) = crlf_relaxed::<_, Error>(rem).unwrap();
+        let (rem, _) = sp::<_, Error>(rem).unwrap();
+        let (rem, _) = char::<_, Error>(rem).unwrap();
+        let (rem, _) = ctl::<_, Error>(rem).unwrap();
+        let (rem, _) = vchar::<_, Error>(rem).unwrap();
+        let (rem, _) = octet::<_, Error>(rem).unwrap();
+        let (rem, _) = lwsp::<_, Error>(rem).unwrap();
+        assert_eq!(rem.fragment(), b"x");
+        assert_eq!(
+            Location::from(&rem),
+            Location {
+                offset: 18,
+                line: 3,
+                column: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_error_location() {
+        let input = Span::new(b"  \r\n  x");
+
+        let err = preceded(lwsp::<_, Error>, crlf::<_, Error>)(input).unwrap_err();
+        assert_eq!(
+            err,
+            nom::Err::Error(Error {
+                location: Location {
+                    offset: 6,
+                    line: 2,
+                    column: 3
+                },
+                kind: ErrorKind::Satisfy,
+            })
+        );
+    }
+
+    #[test]
+    fn test_spanned() {
+        let input = Span::new(b"\r\n12ab");
+
+        let (rem, _) = crlf::<_, Error>(input).unwrap();
+        let (_, spanned) = spanned(many1_count(digit::<_, Error>))(rem).unwrap();
+        assert_eq!(spanned.span.fragment(), b"12");
+        assert_eq!(spanned.value, 2);
+        assert_eq!(
+            spanned.location(),
+            Location {
+                offset: 2,
+                line: 2,
+                column: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,8 @@
+//! ABNF Core Rules (RFC5234 B.1.) for nom 8
+//!
+//! nom 8 replaced `InputIter`, `InputLength` and `Slice` with the `Input` trait.
+//! This module mirrors the nom 7 API of [`crate::complete`] and [`crate::streaming`],
+//! so parsers can be migrated one at a time.
+
+pub mod complete;
+pub mod streaming;
@@ -0,0 +1,333 @@
+//! ABNF Core Rules (RFC5234 B.1.)
+
+use nom8::{
+    branch::alt,
+    bytes::complete::take,
+    character::complete::satisfy,
+    combinator::{opt, recognize},
+    error::{ErrorKind, ParseError},
+    multi::many0_count,
+    sequence::{pair, terminated},
+    AsChar, Err as OutCome, IResult, Input, Offset, Parser,
+};
+
+use crate::{
+    is_alpha, is_bit, is_char, is_cr, is_ctl, is_digit, is_dquote, is_hexdig, is_htab, is_lf,
+    is_sp, is_vchar, is_wsp, NewlinePolicy,
+};
+
+/// ALPHA = %x41-5A / %x61-7A ; A-Z / a-z
+pub fn alpha<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_alpha)(input)
+}
+
+/// BIT = "0" / "1"
+pub fn bit<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_bit)(input)
+}
+
+/// CHAR = %x01-7F ; any 7-bit US-ASCII character, excluding NUL
+pub fn char<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_char)(input)
+}
+
+/// Carriage return
+///
+/// CR = %x0D
+pub fn cr<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_cr)(input)
+}
+
+/// Internet standard newline
+///
+/// CRLF = CR LF
+///
+/// Note: this variant will strictly expect "\r\n".
+/// Use [crlf_relaxed](fn.crlf_relaxed.html) to accept "\r\n" as well as only "\n".
+pub fn crlf<I, E>(input: I) -> IResult<I, (char, char), E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    pair(satisfy(is_cr), satisfy(is_lf)).parse(input)
+}
+
+/// Newline, with and without "\r".
+pub fn crlf_relaxed<I, E>(input: I) -> IResult<I, (Option<char>, char), E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    pair(opt(satisfy(is_cr)), satisfy(is_lf)).parse(input)
+}
+
+/// Newline according to `policy`.
+///
+/// Returns the recognized line ending.
+pub fn crlf_with<I, E>(policy: NewlinePolicy) -> impl FnMut(I) -> IResult<I, I, E>
+where
+    I: Input + Offset,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    move |input: I| match policy {
+        NewlinePolicy::Strict => recognize(pair(cr, lf)).parse(input),
+        NewlinePolicy::AllowLf => recognize(pair(opt(cr), lf)).parse(input),
+        NewlinePolicy::AllowCr => recognize(pair(cr, opt(lf))).parse(input),
+        NewlinePolicy::Any => alt((recognize(pair(cr, opt(lf))), recognize(lf))).parse(input),
+    }
+}
+
+/// CTL = %x00-1F / %x7F ; controls
+pub fn ctl<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_ctl)(input)
+}
+
+/// DIGIT = %x30-39 ; 0-9
+pub fn digit<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_digit)(input)
+}
+
+/// Double Quote
+///
+/// DQUOTE = %x22
+pub fn dquote<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_dquote)(input)
+}
+
+/// HEXDIG = DIGIT / "A" / "B" / "C" / "D" / "E" / "F"
+pub fn hexdig<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_hexdig)(input)
+}
+
+/// Horizontal tab
+///
+/// HTAB = %x09
+pub fn htab<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_htab)(input)
+}
+
+/// Linefeed
+///
+/// LF = %x0A
+pub fn lf<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_lf)(input)
+}
+
+/// Use of this linear-white-space rule permits lines containing only white
+/// space that are no longer legal in mail headers and have caused
+/// interoperability problems in other contexts.
+///
+/// Do not use when defining mail headers and use with caution in other contexts.
+///
+/// LWSP = *(WSP / CRLF WSP)
+pub fn lwsp<I, E>(input: I) -> IResult<I, I, E>
+where
+    I: Input + Offset,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    // code as equivalent avoid branching LWSP = *([CRLF] WSP)
+    recognize(many0_count(terminated(opt(crlf), wsp))).parse(input)
+}
+
+/// LWSP with line endings according to `policy`.
+///
+/// LWSP = *(WSP / newline WSP)
+///
+/// See [lwsp](fn.lwsp.html) for why this rule should be used with caution.
+pub fn lwsp_with<I, E>(policy: NewlinePolicy) -> impl FnMut(I) -> IResult<I, I, E>
+where
+    I: Input + Offset,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    move |input: I| recognize(many0_count(terminated(opt(crlf_with(policy)), wsp))).parse(input)
+}
+
+/// OCTET = %x00-FF ; 8 bits of data
+pub fn octet<I, E>(input: I) -> IResult<I, u8, E>
+where
+    I: Input<Item = u8>,
+    E: ParseError<I>,
+{
+    match input.iter_elements().next() {
+        None => Err(OutCome::Error(E::from_error_kind(
+            input,
+            ErrorKind::Complete,
+        ))),
+        Some(b) => Ok((input.take_from(1), b)),
+    }
+}
+
+/// n*n OCTET ; exactly `n` bytes of data
+pub fn octets<I, E>(n: usize) -> impl FnMut(I) -> IResult<I, I, E>
+where
+    I: Input<Item = u8>,
+    E: ParseError<I>,
+{
+    take(n)
+}
+
+/// SP = %x20
+pub fn sp<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_sp)(input)
+}
+
+/// VCHAR = %x21-7E ; visible (printing) characters
+pub fn vchar<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_vchar)(input)
+}
+
+/// WSP = SP / HTAB ; white space
+pub fn wsp<I, E>(input: I) -> IResult<I, char, E>
+where
+    I: Input,
+    <I as Input>::Item: AsChar,
+    E: ParseError<I>,
+{
+    satisfy(is_wsp)(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use nom8::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn test_alpha() {
+        assert!(alpha::<_, Error<&str>>("").is_err());
+
+        assert!(alpha::<_, Error<&str>>("`").is_err());
+        assert_eq!(alpha::<_, Error<&str>>("a"), Ok(("", 'a')));
+        assert_eq!(alpha::<_, Error<&str>>("Z"), Ok(("", 'Z')));
+        assert!(alpha::<_, Error<&str>>("[").is_err());
+
+        assert_eq!(alpha::<_, Error<_>>(&b"a"[..]), Ok((&b""[..], 'a')));
+    }
+
+    #[test]
+    fn test_crlf() {
+        assert!(crlf::<_, Error<&str>>("").is_err());
+        assert!(crlf::<_, Error<&str>>("\r").is_err());
+        assert!(crlf::<_, Error<&str>>("\n").is_err());
+
+        assert_eq!(crlf::<_, Error<&str>>("\r\n"), Ok(("", ('\r', '\n'))));
+    }
+
+    #[test]
+    fn test_crlf_relaxed() {
+        assert!(crlf_relaxed::<_, Error<&str>>("").is_err());
+        assert!(crlf_relaxed::<_, Error<&str>>("\r").is_err());
+
+        assert_eq!(crlf_relaxed::<_, Error<&str>>("\n"), Ok(("", (None, '\n'))));
+        assert_eq!(
+            crlf_relaxed::<_, Error<&str>>("\r\n"),
+            Ok(("", (Some('\r'), '\n')))
+        );
+    }
+
+    #[test]
+    fn test_crlf_with() {
+        assert!(crlf_with::<_, Error<&str>>(NewlinePolicy::Strict)("\n").is_err());
+        assert_eq!(
+            crlf_with::<_, Error<&str>>(NewlinePolicy::AllowLf)("\nx"),
+            Ok(("x", "\n"))
+        );
+        assert_eq!(
+            crlf_with::<_, Error<&str>>(NewlinePolicy::AllowCr)("\rx"),
+            Ok(("x", "\r"))
+        );
+        assert_eq!(
+            crlf_with::<_, Error<&str>>(NewlinePolicy::Any)("\r\nx"),
+            Ok(("x", "\r\n"))
+        );
+        assert_eq!(
+            lwsp_with::<_, Error<&str>>(NewlinePolicy::Any)(" \r \n x"),
+            Ok(("x", " \r \n "))
+        );
+    }
+
+    #[test]
+    fn test_lwsp() {
+        assert_eq!(lwsp::<_, Error<&str>>(""), Ok(("", "")));
+        assert_eq!(lwsp::<_, Error<&str>>(" \t x"), Ok(("x", " \t ")));
+        assert_eq!(lwsp::<_, Error<&str>>(" \r\n x"), Ok(("x", " \r\n ")));
+        assert_eq!(lwsp::<_, Error<&str>>(" \r\nx"), Ok(("\r\nx", " ")));
+    }
+
+    #[test]
+    fn test_octet() {
+        assert!(octet::<_, Error<&[u8]>>(b"").is_err());
+        assert_eq!(octet::<_, Error<&[u8]>>(b"\xff"), Ok((&b""[..], 0xff)));
+
+        assert!(octets::<_, Error<&[u8]>>(2)(b"a").is_err());
+        assert_eq!(
+            octets::<_, Error<&[u8]>>(2)(b"abc"),
+            Ok((&b"c"[..], &b"ab"[..]))
+        );
+    }
+}
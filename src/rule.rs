@@ -0,0 +1,451 @@
+//! Core rules as values
+//!
+//! [`CoreRule`] enumerates all rules of RFC5234 B.1. together with their names,
+//! definitions, predicates and parsers, so that tooling does not need to hard-code them.
+//! [`classify`] does the reverse and returns all rules which match a byte.
+
+use core::fmt::{Display, Formatter};
+
+use nom::{combinator::recognize, error::ParseError, IResult};
+
+use crate::{
+    complete, is_alpha, is_bit, is_char, is_cr, is_ctl, is_digit, is_dquote, is_hexdig, is_htab,
+    is_lf, is_octet, is_sp, is_vchar, is_wsp, streaming, CharClass, Profile,
+};
+
+/// A recognizing parser for a core rule, as returned by [`CoreRule::complete`],
+/// [`CoreRule::streaming`] and their `_with` variants.
+pub type RuleParser<'a, E> = fn(&'a [u8]) -> IResult<&'a [u8], &'a [u8], E>;
+
+/// A core rule (RFC5234 B.1.)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CoreRule {
+    Alpha,
+    Bit,
+    Char,
+    Cr,
+    Crlf,
+    Ctl,
+    Digit,
+    Dquote,
+    Hexdig,
+    Htab,
+    Lf,
+    Lwsp,
+    Octet,
+    Sp,
+    Vchar,
+    Wsp,
+}
+
+impl CoreRule {
+    /// All core rules in the order of RFC5234 B.1.
+    pub const ALL: [CoreRule; 16] = [
+        CoreRule::Alpha,
+        CoreRule::Bit,
+        CoreRule::Char,
+        CoreRule::Cr,
+        CoreRule::Crlf,
+        CoreRule::Ctl,
+        CoreRule::Digit,
+        CoreRule::Dquote,
+        CoreRule::Hexdig,
+        CoreRule::Htab,
+        CoreRule::Lf,
+        CoreRule::Lwsp,
+        CoreRule::Octet,
+        CoreRule::Sp,
+        CoreRule::Vchar,
+        CoreRule::Wsp,
+    ];
+
+    /// Name of the rule as written in ABNF, e.g., `ALPHA`.
+    pub fn name(self) -> &'static str {
+        match self {
+            CoreRule::Alpha => "ALPHA",
+            CoreRule::Bit => "BIT",
+            CoreRule::Char => "CHAR",
+            CoreRule::Cr => "CR",
+            CoreRule::Crlf => "CRLF",
+            CoreRule::Ctl => "CTL",
+            CoreRule::Digit => "DIGIT",
+            CoreRule::Dquote => "DQUOTE",
+            CoreRule::Hexdig => "HEXDIG",
+            CoreRule::Htab => "HTAB",
+            CoreRule::Lf => "LF",
+            CoreRule::Lwsp => "LWSP",
+            CoreRule::Octet => "OCTET",
+            CoreRule::Sp => "SP",
+            CoreRule::Vchar => "VCHAR",
+            CoreRule::Wsp => "WSP",
+        }
+    }
+
+    /// Look up a rule by name. ABNF rule names are case-insensitive.
+    pub fn from_name(name: &str) -> Option<CoreRule> {
+        CoreRule::ALL
+            .into_iter()
+            .find(|rule| rule.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Definition of the rule as written in RFC5234, e.g., `%x41-5A / %x61-7A`.
+    pub fn definition(self) -> &'static str {
+        match self {
+            CoreRule::Alpha => "%x41-5A / %x61-7A",
+            CoreRule::Bit => "\"0\" / \"1\"",
+            CoreRule::Char => "%x01-7F",
+            CoreRule::Cr => "%x0D",
+            CoreRule::Crlf => "CR LF",
+            CoreRule::Ctl => "%x00-1F / %x7F",
+            CoreRule::Digit => "%x30-39",
+            CoreRule::Dquote => "%x22",
+            CoreRule::Hexdig => "DIGIT / \"A\" / \"B\" / \"C\" / \"D\" / \"E\" / \"F\"",
+            CoreRule::Htab => "%x09",
+            CoreRule::Lf => "%x0A",
+            CoreRule::Lwsp => "*(WSP / CRLF WSP)",
+            CoreRule::Octet => "%x00-FF",
+            CoreRule::Sp => "%x20",
+            CoreRule::Vchar => "%x21-7E",
+            CoreRule::Wsp => "SP / HTAB",
+        }
+    }
+
+    /// Predicate of the rule.
+    ///
+    /// Returns `None` for CRLF and LWSP, which match more than one character.
+    pub fn predicate(self) -> Option<fn(u8) -> bool> {
+        match self {
+            CoreRule::Alpha => Some(is_alpha),
+            CoreRule::Bit => Some(is_bit),
+            CoreRule::Char => Some(is_char),
+            CoreRule::Cr => Some(is_cr),
+            CoreRule::Crlf => None,
+            CoreRule::Ctl => Some(is_ctl),
+            CoreRule::Digit => Some(is_digit),
+            CoreRule::Dquote => Some(is_dquote),
+            CoreRule::Hexdig => Some(is_hexdig),
+            CoreRule::Htab => Some(is_htab),
+            CoreRule::Lf => Some(is_lf),
+            CoreRule::Lwsp => None,
+            CoreRule::Octet => Some(is_octet),
+            CoreRule::Sp => Some(is_sp),
+            CoreRule::Vchar => Some(is_vchar),
+            CoreRule::Wsp => Some(is_wsp),
+        }
+    }
+
+    /// Character class of the rule.
+    ///
+    /// Returns `None` for CRLF and LWSP, which match more than one character.
+    pub fn char_class(self) -> Option<CharClass> {
+        match self {
+            CoreRule::Alpha => Some(CharClass::Alpha),
+            CoreRule::Bit => Some(CharClass::Bit),
+            CoreRule::Char => Some(CharClass::Char),
+            CoreRule::Cr => Some(CharClass::Cr),
+            CoreRule::Crlf => None,
+            CoreRule::Ctl => Some(CharClass::Ctl),
+            CoreRule::Digit => Some(CharClass::Digit),
+            CoreRule::Dquote => Some(CharClass::Dquote),
+            CoreRule::Hexdig => Some(CharClass::Hexdig),
+            CoreRule::Htab => Some(CharClass::Htab),
+            CoreRule::Lf => Some(CharClass::Lf),
+            CoreRule::Lwsp => None,
+            CoreRule::Octet => Some(CharClass::Octet),
+            CoreRule::Sp => Some(CharClass::Sp),
+            CoreRule::Vchar => Some(CharClass::Vchar),
+            CoreRule::Wsp => Some(CharClass::Wsp),
+        }
+    }
+
+    /// Parser from [`complete`] which returns the recognized input.
+    pub fn complete<'a, E: ParseError<&'a [u8]>>(self) -> RuleParser<'a, E> {
+        match self {
+            CoreRule::Alpha => |i| recognize(complete::alpha)(i),
+            CoreRule::Bit => |i| recognize(complete::bit)(i),
+            CoreRule::Char => |i| recognize(complete::char)(i),
+            CoreRule::Cr => |i| recognize(complete::cr)(i),
+            CoreRule::Crlf => |i| recognize(complete::crlf)(i),
+            CoreRule::Ctl => |i| recognize(complete::ctl)(i),
+            CoreRule::Digit => |i| recognize(complete::digit)(i),
+            CoreRule::Dquote => |i| recognize(complete::dquote)(i),
+            CoreRule::Hexdig => |i| recognize(complete::hexdig)(i),
+            CoreRule::Htab => |i| recognize(complete::htab)(i),
+            CoreRule::Lf => |i| recognize(complete::lf)(i),
+            CoreRule::Lwsp => complete::lwsp,
+            CoreRule::Octet => |i| recognize(complete::octet)(i),
+            CoreRule::Sp => |i| recognize(complete::sp)(i),
+            CoreRule::Vchar => |i| recognize(complete::vchar)(i),
+            CoreRule::Wsp => |i| recognize(complete::wsp)(i),
+        }
+    }
+
+    /// Parser from [`complete`] which returns the recognized input.
+    ///
+    /// HEXDIG and LWSP are read according to `profile`, see [`Profile`].
+    pub fn complete_with<'a, E: ParseError<&'a [u8]>>(self, profile: Profile) -> RuleParser<'a, E> {
+        match (self, profile) {
+            (CoreRule::Hexdig, Profile::Strict) => |i| recognize(complete::strict::hexdig)(i),
+            (CoreRule::Lwsp, Profile::Strict) => complete::strict::lwsp,
+            _ => self.complete(),
+        }
+    }
+
+    /// Parser from [`streaming`] which returns the recognized input.
+    pub fn streaming<'a, E: ParseError<&'a [u8]>>(self) -> RuleParser<'a, E> {
+        match self {
+            CoreRule::Alpha => |i| recognize(streaming::alpha)(i),
+            CoreRule::Bit => |i| recognize(streaming::bit)(i),
+            CoreRule::Char => |i| recognize(streaming::char)(i),
+            CoreRule::Cr => |i| recognize(streaming::cr)(i),
+            CoreRule::Crlf => |i| recognize(streaming::crlf)(i),
+            CoreRule::Ctl => |i| recognize(streaming::ctl)(i),
+            CoreRule::Digit => |i| recognize(streaming::digit)(i),
+            CoreRule::Dquote => |i| recognize(streaming::dquote)(i),
+            CoreRule::Hexdig => |i| recognize(streaming::hexdig)(i),
+            CoreRule::Htab => |i| recognize(streaming::htab)(i),
+            CoreRule::Lf => |i| recognize(streaming::lf)(i),
+            CoreRule::Lwsp => streaming::lwsp,
+            CoreRule::Octet => |i| recognize(streaming::octet)(i),
+            CoreRule::Sp => |i| recognize(streaming::sp)(i),
+            CoreRule::Vchar => |i| recognize(streaming::vchar)(i),
+            CoreRule::Wsp => |i| recognize(streaming::wsp)(i),
+        }
+    }
+
+    /// Parser from [`streaming`] which returns the recognized input.
+    ///
+    /// HEXDIG and LWSP are read according to `profile`, see [`Profile`].
+    pub fn streaming_with<'a, E: ParseError<&'a [u8]>>(
+        self,
+        profile: Profile,
+    ) -> RuleParser<'a, E> {
+        match (self, profile) {
+            (CoreRule::Hexdig, Profile::Strict) => |i| recognize(streaming::strict::hexdig)(i),
+            (CoreRule::Lwsp, Profile::Strict) => streaming::strict::lwsp,
+            _ => self.streaming(),
+        }
+    }
+}
+
+impl Display for CoreRule {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A set of core rules
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CoreRuleSet(u16);
+
+impl CoreRuleSet {
+    /// The empty set.
+    pub const fn new() -> Self {
+        CoreRuleSet(0)
+    }
+
+    pub fn insert(&mut self, rule: CoreRule) {
+        self.0 |= 1 << rule as u16;
+    }
+
+    pub fn remove(&mut self, rule: CoreRule) {
+        self.0 &= !(1 << rule as u16);
+    }
+
+    pub fn contains(self, rule: CoreRule) -> bool {
+        self.0 & (1 << rule as u16) != 0
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Rules in the set in the order of RFC5234 B.1.
+    pub fn iter(self) -> impl Iterator<Item = CoreRule> {
+        CoreRule::ALL
+            .into_iter()
+            .filter(move |rule| self.contains(*rule))
+    }
+}
+
+impl FromIterator<CoreRule> for CoreRuleSet {
+    fn from_iter<T: IntoIterator<Item = CoreRule>>(iter: T) -> Self {
+        let mut set = CoreRuleSet::new();
+
+        for rule in iter {
+            set.insert(rule);
+        }
+
+        set
+    }
+}
+
+/// Prints the rule names separated by `, `.
+impl Display for CoreRuleSet {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        for (i, rule) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+
+            f.write_str(rule.name())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// All core rules which match the single byte `b`.
+///
+/// CRLF and LWSP are never included.
+pub fn classify(b: u8) -> CoreRuleSet {
+    CoreRule::ALL
+        .into_iter()
+        .filter(|rule| rule.predicate().is_some_and(|predicate| predicate(b)))
+        .collect()
+}
+
+/// Table of [`classify`] for all bytes, one `%xHH RULE, ...` line per byte.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClassifyTable;
+
+impl Display for ClassifyTable {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        for b in 0..=255 {
+            writeln!(f, "%x{b:02X} {}", classify(b))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::{error::Error, Err as OutCome};
+
+    use super::*;
+
+    #[test]
+    fn test_name() {
+        for rule in CoreRule::ALL {
+            assert_eq!(CoreRule::from_name(rule.name()), Some(rule));
+        }
+
+        assert_eq!(CoreRule::from_name("vchar"), Some(CoreRule::Vchar));
+        assert_eq!(CoreRule::from_name("VCHARS"), None);
+    }
+
+    #[test]
+    fn test_classify() {
+        assert!(classify(0x09).iter().eq([
+            CoreRule::Char,
+            CoreRule::Ctl,
+            CoreRule::Htab,
+            CoreRule::Octet,
+            CoreRule::Wsp
+        ]));
+        assert!(classify(0xFF).iter().eq([CoreRule::Octet]));
+
+        for b in 0..=255 {
+            for rule in CoreRule::ALL {
+                assert_eq!(
+                    classify(b).contains(rule),
+                    rule.predicate().is_some_and(|predicate| predicate(b))
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_classify_display() {
+        use alloc::string::ToString;
+
+        assert_eq!(
+            classify(b'a').to_string(),
+            "ALPHA, CHAR, HEXDIG, OCTET, VCHAR"
+        );
+        assert_eq!(CoreRuleSet::new().to_string(), "");
+
+        let table = ClassifyTable.to_string();
+        assert_eq!(table.lines().count(), 256);
+        assert!(table.contains("\n%x09 CHAR, CTL, HTAB, OCTET, WSP\n"));
+        assert!(table.ends_with("%xFF OCTET\n"));
+    }
+
+    #[test]
+    fn test_predicate() {
+        for rule in CoreRule::ALL {
+            assert_eq!(rule.predicate().is_some(), rule.char_class().is_some());
+
+            if let (Some(predicate), Some(class)) = (rule.predicate(), rule.char_class()) {
+                for b in 0..=255 {
+                    assert_eq!(predicate(b), class.contains(b), "{rule} {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parser() {
+        for rule in CoreRule::ALL {
+            if let Some(predicate) = rule.predicate() {
+                for b in 0..=255u8 {
+                    let input = [b, b'!'];
+
+                    match rule.complete::<Error<_>>()(&input) {
+                        Ok((rem, out)) => {
+                            assert!(predicate(b), "{rule} {b}");
+                            assert_eq!((rem, out), (&input[1..], &input[..1]));
+                        }
+                        Err(_) => assert!(!predicate(b), "{rule} {b}"),
+                    }
+                }
+            }
+        }
+
+        let crlf = CoreRule::Crlf.complete::<Error<_>>();
+        assert_eq!(crlf(b"\r\nx"), Ok((&b"x"[..], &b"\r\n"[..])));
+        assert!(crlf(b"\n").is_err());
+
+        let lwsp = CoreRule::Lwsp.streaming::<Error<_>>();
+        assert_eq!(lwsp(b" \r\n x"), Ok((&b"x"[..], &b" \r\n "[..])));
+        assert!(matches!(lwsp(b" \r"), Err(OutCome::Incomplete(_))));
+    }
+
+    #[test]
+    fn test_parser_with() {
+        for rule in CoreRule::ALL {
+            for b in 0..=255u8 {
+                let input = [b, b'!'];
+
+                assert_eq!(
+                    rule.complete_with::<Error<_>>(Profile::Lenient)(&input),
+                    rule.complete::<Error<_>>()(&input)
+                );
+                assert_eq!(
+                    rule.streaming_with::<Error<_>>(Profile::Lenient)(&input),
+                    rule.streaming::<Error<_>>()(&input)
+                );
+            }
+        }
+
+        let hexdig = CoreRule::Hexdig.complete_with::<Error<_>>(Profile::Strict);
+        assert_eq!(hexdig(b"Fx"), Ok((&b"x"[..], &b"F"[..])));
+        assert!(hexdig(b"fx").is_err());
+
+        let lwsp = CoreRule::Lwsp.complete_with::<Error<_>>(Profile::Strict);
+        assert_eq!(lwsp(b" \r\n \r\nx"), Ok((&b"\r\n \r\nx"[..], &b" "[..])));
+        let lwsp = CoreRule::Lwsp.complete_with::<Error<_>>(Profile::Lenient);
+        assert_eq!(lwsp(b" \r\n \r\nx"), Ok((&b"\r\nx"[..], &b" \r\n "[..])));
+
+        let hexdig = CoreRule::Hexdig.streaming_with::<Error<_>>(Profile::Strict);
+        assert!(hexdig(b"a").is_err());
+        let lwsp = CoreRule::Lwsp.streaming_with::<Error<_>>(Profile::Strict);
+        assert_eq!(lwsp(b" \r\n \r\nx"), Ok((&b"\r\n \r\nx"[..], &b" "[..])));
+    }
+}
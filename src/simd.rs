@@ -0,0 +1,269 @@
+//! Bulk validation of character classes
+//!
+//! The functions in this module are equivalent to checking every byte with the
+//! `is_*` predicates, but use SSE2 or AVX2 on x86 and x86_64 for all classes. AVX2 is
+//! detected at runtime when the `std` feature is enabled. Other targets use a scalar
+//! fallback.
+
+use crate::CharClass;
+
+/// Whether all bytes are VCHAR.
+pub fn is_all_vchar(input: &[u8]) -> bool {
+    find_first_not(CharClass::Vchar, input).is_none()
+}
+
+/// Whether all bytes are CHAR.
+pub fn is_all_char(input: &[u8]) -> bool {
+    find_first_not(CharClass::Char, input).is_none()
+}
+
+/// Offset of the first byte which is not in `class`.
+pub fn find_first_not(class: CharClass, input: &[u8]) -> Option<usize> {
+    find(class.ranges(), false, input)
+}
+
+/// Offset of the first CTL.
+pub fn find_ctl(input: &[u8]) -> Option<usize> {
+    find(CharClass::Ctl.ranges(), true, input)
+}
+
+/// Offset of the first byte which is (`inside`) or is not (`!inside`) in one of `ranges`.
+fn find(ranges: &[(u8, u8)], inside: bool, input: &[u8]) -> Option<usize> {
+    #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+    if std::is_x86_feature_detected!("avx2") {
+        // SAFETY: AVX2 is available.
+        return unsafe { x86::find_avx2(ranges, inside, input) };
+    }
+
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse2"
+    ))]
+    {
+        // SAFETY: SSE2 is enabled at compile time.
+        unsafe { x86::find_sse2(ranges, inside, input) }
+    }
+
+    #[cfg(not(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse2"
+    )))]
+    {
+        scalar::find(ranges, inside, input)
+    }
+}
+
+mod scalar {
+    pub(super) fn find(ranges: &[(u8, u8)], inside: bool, input: &[u8]) -> Option<usize> {
+        input
+            .iter()
+            .position(|b| ranges.iter().any(|(lo, hi)| (lo..=hi).contains(&b)) == inside)
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+
+    use super::scalar;
+
+    // A byte `b` is in `lo..=hi` iff `b - lo <= hi - lo` (wrapping, unsigned). SSE2 and
+    // AVX2 only compare unsigned bytes for equality, so `x <= k` is `max(x, k) == k`.
+    // A class is the union of at most `MAX_RANGES` such ranges.
+
+    const MAX_RANGES: usize = 3;
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn find_sse2(
+        ranges: &[(u8, u8)],
+        inside: bool,
+        input: &[u8],
+    ) -> Option<usize> {
+        if ranges.len() > MAX_RANGES {
+            return scalar::find(ranges, inside, input);
+        }
+
+        let mut bounds = [(_mm_setzero_si128(), _mm_setzero_si128()); MAX_RANGES];
+        for (bound, &(lo, hi)) in bounds.iter_mut().zip(ranges) {
+            *bound = (
+                _mm_set1_epi8(lo as i8),
+                _mm_set1_epi8(hi.wrapping_sub(lo) as i8),
+            );
+        }
+        let bounds = &bounds[..ranges.len()];
+
+        let mut chunks = input.chunks_exact(16);
+        let mut offset = 0;
+
+        for chunk in &mut chunks {
+            let v = _mm_loadu_si128(chunk.as_ptr() as *const __m128i);
+            let mut matches = _mm_setzero_si128();
+            for &(lo, span) in bounds {
+                let d = _mm_sub_epi8(v, lo);
+                matches = _mm_or_si128(matches, _mm_cmpeq_epi8(_mm_max_epu8(d, span), span));
+            }
+            let mut mask = _mm_movemask_epi8(matches) as u32 & 0xFFFF;
+            if !inside {
+                mask = !mask & 0xFFFF;
+            }
+
+            if mask != 0 {
+                return Some(offset + mask.trailing_zeros() as usize);
+            }
+
+            offset += 16;
+        }
+
+        scalar::find(ranges, inside, chunks.remainder()).map(|i| offset + i)
+    }
+
+    #[cfg(feature = "std")]
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn find_avx2(
+        ranges: &[(u8, u8)],
+        inside: bool,
+        input: &[u8],
+    ) -> Option<usize> {
+        if ranges.len() > MAX_RANGES {
+            return scalar::find(ranges, inside, input);
+        }
+
+        let mut bounds = [(_mm256_setzero_si256(), _mm256_setzero_si256()); MAX_RANGES];
+        for (bound, &(lo, hi)) in bounds.iter_mut().zip(ranges) {
+            *bound = (
+                _mm256_set1_epi8(lo as i8),
+                _mm256_set1_epi8(hi.wrapping_sub(lo) as i8),
+            );
+        }
+        let bounds = &bounds[..ranges.len()];
+
+        let mut chunks = input.chunks_exact(32);
+        let mut offset = 0;
+
+        for chunk in &mut chunks {
+            let v = _mm256_loadu_si256(chunk.as_ptr() as *const __m256i);
+            let mut matches = _mm256_setzero_si256();
+            for &(lo, span) in bounds {
+                let d = _mm256_sub_epi8(v, lo);
+                matches =
+                    _mm256_or_si256(matches, _mm256_cmpeq_epi8(_mm256_max_epu8(d, span), span));
+            }
+            let mut mask = _mm256_movemask_epi8(matches) as u32;
+            if !inside {
+                mask = !mask;
+            }
+
+            if mask != 0 {
+                return Some(offset + mask.trailing_zeros() as usize);
+            }
+
+            offset += 32;
+        }
+
+        find_sse2(ranges, inside, chunks.remainder()).map(|i| offset + i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::is_ctl;
+
+    const CLASSES: [CharClass; 14] = [
+        CharClass::Alpha,
+        CharClass::Bit,
+        CharClass::Char,
+        CharClass::Cr,
+        CharClass::Ctl,
+        CharClass::Digit,
+        CharClass::Dquote,
+        CharClass::Hexdig,
+        CharClass::Htab,
+        CharClass::Lf,
+        CharClass::Octet,
+        CharClass::Sp,
+        CharClass::Vchar,
+        CharClass::Wsp,
+    ];
+
+    fn inputs() -> impl Iterator<Item = ([u8; 70], usize, u8)> {
+        // Every length up to two AVX2 chunks and a remainder, with every byte value at a
+        // few positions.
+        (0..70usize).flat_map(|len| {
+            (0..=255u8).flat_map(move |b| {
+                [0, len / 2, len.saturating_sub(1)]
+                    .into_iter()
+                    .map(move |at| {
+                        let mut input = [b'a'; 70];
+                        if at < len {
+                            input[at] = b;
+                        }
+                        (input, len, b)
+                    })
+            })
+        })
+    }
+
+    #[test]
+    fn test_find_first_not() {
+        for (input, len, _) in inputs() {
+            let input = &input[..len];
+
+            for class in CLASSES {
+                assert_eq!(
+                    find_first_not(class, input),
+                    input.iter().position(|&b| !class.contains(b)),
+                    "{class:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_ctl() {
+        for (input, len, _) in inputs() {
+            let input = &input[..len];
+
+            assert_eq!(find_ctl(input), input.iter().position(|&b| is_ctl(b)));
+        }
+    }
+
+    #[test]
+    fn test_is_all() {
+        assert!(is_all_vchar(b""));
+        assert!(is_all_vchar(&[b'~'; 1000]));
+        assert!(!is_all_vchar(b"Subject: x"));
+        assert!(is_all_char(b"Subject: x\r\n"));
+        assert!(!is_all_char(b"\x00"));
+        assert!(!is_all_char(&[0x80; 64]));
+    }
+
+    #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+    #[test]
+    fn test_x86() {
+        for (input, len, _) in inputs() {
+            let input = &input[..len];
+
+            for class in CLASSES {
+                for inside in [false, true] {
+                    let expected = scalar::find(class.ranges(), inside, input);
+
+                    if std::is_x86_feature_detected!("sse2") {
+                        unsafe {
+                            assert_eq!(x86::find_sse2(class.ranges(), inside, input), expected);
+                        }
+                    }
+
+                    if std::is_x86_feature_detected!("avx2") {
+                        unsafe {
+                            assert_eq!(x86::find_avx2(class.ranges(), inside, input), expected);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,292 @@
+//! Incremental driver for [`streaming`](crate::streaming) parsers
+//!
+//! [`StreamParser`] buffers chunks, re-runs a parser when it returned `Incomplete` and
+//! enough data arrived, and drops consumed bytes. Parsers fail with nom's [`Error`] by
+//! default, other error types are supported via [`StreamParseError`].
+//!
+//! ```
+//! use abnf_core::{
+//!     stream::StreamParser,
+//!     streaming::{crlf, lwsp},
+//! };
+//! use nom::{combinator::value, error::Error, sequence::terminated};
+//!
+//! let mut parser = StreamParser::new(|input| {
+//!     value((), terminated(lwsp::<_, Error<_>>, crlf))(input)
+//! });
+//!
+//! parser.feed(b" \r\n ").unwrap();
+//! assert_eq!(parser.parse().unwrap(), None);
+//!
+//! // A WSP could still continue LWSP.
+//! parser.feed(b"\r\n").unwrap();
+//! assert_eq!(parser.parse().unwrap(), None);
+//!
+//! parser.feed(b"x").unwrap();
+//! assert_eq!(parser.parse().unwrap(), Some((&b" \r\n \r\n"[..], ())));
+//! assert_eq!(parser.buffered(), b"x");
+//! ```
+
+use alloc::vec::Vec;
+use core::{convert::Infallible, fmt, marker::PhantomData};
+
+use nom::{
+    error::{Error, ParseError, VerboseError},
+    Err as OutCome, IResult, Needed,
+};
+
+/// Default for [`StreamParser::max_len`]
+pub const DEFAULT_MAX_LEN: usize = 64 * 1024;
+
+/// Error type of a parser driven by a [`StreamParser`].
+///
+/// Parse errors usually borrow the input, which is the buffer of the [`StreamParser`].
+/// This trait maps an error type to the same error type for any lifetime of the buffer.
+pub trait StreamParseError {
+    /// The error type for input borrowed for `'a`.
+    type Error<'a>: ParseError<&'a [u8]>;
+
+    /// Offset of `error`, relative to the `input` the parser was run on.
+    fn offset(error: &Self::Error<'_>, input: &[u8]) -> usize;
+}
+
+impl StreamParseError for Error<&[u8]> {
+    type Error<'a> = Error<&'a [u8]>;
+
+    fn offset(error: &Error<&[u8]>, input: &[u8]) -> usize {
+        input.len() - error.input.len()
+    }
+}
+
+/// The offset is the offset of the innermost error.
+impl StreamParseError for VerboseError<&[u8]> {
+    type Error<'a> = VerboseError<&'a [u8]>;
+
+    fn offset(error: &VerboseError<&[u8]>, input: &[u8]) -> usize {
+        error
+            .errors
+            .first()
+            .map_or(0, |(rest, _)| input.len() - rest.len())
+    }
+}
+
+/// Error of a [`StreamParser`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamError<E> {
+    /// The parser failed at `offset`, relative to the first unparsed byte.
+    Parse { offset: usize, error: E },
+    /// More than the maximum number of bytes would need to be buffered.
+    Overflow,
+}
+
+impl<E: fmt::Display> fmt::Display for StreamError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Parse { offset, error } => write!(f, "{} at offset {}", error, offset),
+            StreamError::Overflow => write!(f, "buffer overflow"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug + fmt::Display> std::error::Error for StreamError<E> {}
+
+/// Result of [`StreamParser::parse`]: the consumed bytes and the output, if any.
+pub type StreamResult<'a, O, E> =
+    Result<Option<(&'a [u8], O)>, StreamError<<E as StreamParseError>::Error<'a>>>;
+
+/// Buffer manager which drives a streaming parser over chunked input.
+///
+/// The parser must not borrow from its input in its output, because the buffer is
+/// compacted between calls. The consumed bytes are returned alongside the output instead.
+pub struct StreamParser<P, E = Error<&'static [u8]>> {
+    parser: P,
+    buf: Vec<u8>,
+    start: usize,
+    needed: usize,
+    max_len: usize,
+    error: PhantomData<fn() -> E>,
+}
+
+impl<P> StreamParser<P> {
+    /// Drive `parser`, which fails with nom's [`Error`], buffering at most
+    /// [`DEFAULT_MAX_LEN`] bytes.
+    pub fn new<O>(parser: P) -> Self
+    where
+        P: for<'a> FnMut(&'a [u8]) -> IResult<&'a [u8], O, Error<&'a [u8]>>,
+    {
+        Self::with_error(parser)
+    }
+}
+
+impl<P, E: StreamParseError> StreamParser<P, E> {
+    /// Drive `parser`, which fails with `E`, buffering at most [`DEFAULT_MAX_LEN`] bytes.
+    ///
+    /// `E` is usually given explicitly, e.g., `StreamParser::<_, VerboseError<&[u8]>>::with_error`.
+    pub fn with_error<O>(parser: P) -> Self
+    where
+        P: for<'a> FnMut(&'a [u8]) -> IResult<&'a [u8], O, E::Error<'a>>,
+    {
+        Self {
+            parser,
+            buf: Vec::new(),
+            start: 0,
+            needed: 0,
+            max_len: DEFAULT_MAX_LEN,
+            error: PhantomData,
+        }
+    }
+
+    /// Set the maximum number of buffered bytes.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Bytes which were fed but not consumed yet.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf[self.start..]
+    }
+
+    /// Append a chunk of input.
+    ///
+    /// Fails only with [`StreamError::Overflow`], so the error does not borrow the buffer.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), StreamError<Infallible>> {
+        if self.start > 0 {
+            self.buf.drain(..self.start);
+            self.start = 0;
+        }
+
+        if self.buf.len() + chunk.len() > self.max_len {
+            return Err(StreamError::Overflow);
+        }
+
+        self.buf.extend_from_slice(chunk);
+
+        Ok(())
+    }
+
+    /// Run the parser on the buffered input.
+    ///
+    /// Returns `Ok(None)` when more input is needed. The parser is not re-run before
+    /// the amount of input requested by `Needed` was fed.
+    pub fn parse<O>(&mut self) -> StreamResult<'_, O, E>
+    where
+        P: for<'a> FnMut(&'a [u8]) -> IResult<&'a [u8], O, E::Error<'a>>,
+    {
+        let input = &self.buf[self.start..];
+
+        if input.len() < self.needed {
+            return Ok(None);
+        }
+
+        match (self.parser)(input) {
+            Ok((rest, output)) => {
+                let start = self.start;
+                let consumed = input.len() - rest.len();
+
+                self.start += consumed;
+                self.needed = 0;
+
+                Ok(Some((&self.buf[start..start + consumed], output)))
+            }
+            Err(OutCome::Incomplete(needed)) => {
+                self.needed = input.len()
+                    + match needed {
+                        Needed::Size(size) => size.get(),
+                        Needed::Unknown => 1,
+                    };
+
+                if self.needed > self.max_len {
+                    return Err(StreamError::Overflow);
+                }
+
+                Ok(None)
+            }
+            Err(OutCome::Error(error) | OutCome::Failure(error)) => Err(StreamError::Parse {
+                offset: E::offset(&error, input),
+                error,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::{combinator::value, error::ErrorKind};
+
+    use super::*;
+    use crate::streaming::{crlf, octets};
+
+    #[test]
+    fn test_chunks() {
+        let mut parser = StreamParser::new(|input| crlf::<_, Error<_>>(input));
+
+        assert_eq!(parser.parse().unwrap(), None);
+        parser.feed(b"\r").unwrap();
+        assert_eq!(parser.parse().unwrap(), None);
+        parser.feed(b"\n\r\n\r").unwrap();
+        assert_eq!(parser.parse().unwrap(), Some((&b"\r\n"[..], ('\r', '\n'))));
+        assert_eq!(parser.parse().unwrap(), Some((&b"\r\n"[..], ('\r', '\n'))));
+        assert_eq!(parser.parse().unwrap(), None);
+        assert_eq!(parser.buffered(), b"\r");
+
+        parser.feed(b"x").unwrap();
+        assert_eq!(
+            parser.parse(),
+            Err(StreamError::Parse {
+                offset: 1,
+                error: Error::new(&b"x"[..], ErrorKind::Satisfy)
+            })
+        );
+    }
+
+    #[test]
+    fn test_verbose_error() {
+        let mut parser = StreamParser::<_, VerboseError<&[u8]>>::with_error(|input| crlf(input));
+
+        parser.feed(b"\rx").unwrap();
+        match parser.parse() {
+            Err(StreamError::Parse { offset, error }) => {
+                assert_eq!(offset, 1);
+                assert_eq!(error.errors.len(), 1);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_needed() {
+        let mut calls = 0;
+        let mut parser = StreamParser::new(|input| {
+            calls += 1;
+            value((), octets::<_, Error<_>>(4))(input)
+        });
+
+        parser.feed(b"a").unwrap();
+        assert_eq!(parser.parse().unwrap(), None);
+        parser.feed(b"b").unwrap();
+        assert_eq!(parser.parse().unwrap(), None);
+        parser.feed(b"cd").unwrap();
+        assert_eq!(parser.parse().unwrap(), Some((&b"abcd"[..], ())));
+        drop(parser);
+
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut parser =
+            StreamParser::new(|input| value((), octets::<_, Error<_>>(4))(input)).max_len(3);
+
+        parser.feed(b"ab").unwrap();
+        assert_eq!(parser.parse(), Err(StreamError::Overflow));
+        match parser.feed(b"cd") {
+            Err(error) => assert_eq!(
+                (error, parser.buffered()),
+                (StreamError::Overflow, &b"ab"[..])
+            ),
+            Ok(()) => panic!("expected overflow"),
+        }
+    }
+}
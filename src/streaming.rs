@@ -1,20 +1,26 @@
 //! ABNF Core Rules (RFC5234 B.1.)
 
-use std::ops::{RangeFrom, RangeTo};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::ops::{RangeFrom, RangeTo};
 
 use nom::{
+    branch::alt,
+    bytes::streaming::take,
     character::streaming::satisfy,
-    combinator::{opt, recognize},
+    combinator::{eof, not, opt, recognize},
     error::ParseError,
-    multi::many0_count,
+    multi::{many0_count, many1_count},
     sequence::{pair, terminated},
-    AsChar, Err as OutCome, IResult, InputIter, InputLength, Needed, Offset, Slice,
+    AsChar, Err as OutCome, IResult, InputIter, InputLength, InputTake, Needed, Offset, Slice,
 };
 
 use crate::{
     is_alpha, is_bit, is_char, is_cr, is_ctl, is_digit, is_dquote, is_hexdig, is_htab, is_lf,
-    is_sp, is_wsp,
+    is_sp, is_vchar, is_wsp, NewlinePolicy,
 };
+#[cfg(feature = "alloc")]
+use crate::{Fold, Lwsp};
 
 /// ALPHA = %x41-5A / %x61-7A ; A-Z / a-z
 pub fn alpha<I, E>(input: I) -> IResult<I, char, E>
@@ -83,6 +89,23 @@ where
     pair(opt(satisfy(is_cr)), satisfy(is_lf))(input)
 }
 
+/// Newline according to `policy`.
+///
+/// Returns the recognized line ending.
+pub fn crlf_with<I, E>(policy: NewlinePolicy) -> impl Fn(I) -> IResult<I, I, E>
+where
+    I: Clone + Offset + InputIter + InputLength + Slice<RangeTo<usize>> + Slice<RangeFrom<usize>>,
+    <I as InputIter>::Item: AsChar,
+    E: ParseError<I>,
+{
+    move |input: I| match policy {
+        NewlinePolicy::Strict => recognize(pair(cr, lf))(input),
+        NewlinePolicy::AllowLf => recognize(pair(opt(cr), lf))(input),
+        NewlinePolicy::AllowCr => recognize(pair(cr, opt(lf)))(input),
+        NewlinePolicy::Any => alt((recognize(pair(cr, opt(lf))), recognize(lf)))(input),
+    }
+}
+
 /// CTL = %x00-1F / %x7F ; controls
 pub fn ctl<I, E>(input: I) -> IResult<I, char, E>
 where
@@ -172,17 +195,158 @@ where
     recognize(many0_count(terminated(opt(crlf), wsp)))(input)
 }
 
+/// LWSP with line endings according to `policy`.
+///
+/// LWSP = *(WSP / newline WSP)
+///
+/// See [lwsp](fn.lwsp.html) for why this rule should be used with caution.
+pub fn lwsp_with<I, E>(policy: NewlinePolicy) -> impl Fn(I) -> IResult<I, I, E>
+where
+    I: Clone
+        + Offset
+        + PartialEq
+        + InputIter
+        + InputLength
+        + Slice<RangeTo<usize>>
+        + Slice<RangeFrom<usize>>,
+    <I as InputIter>::Item: AsChar,
+    E: ParseError<I>,
+{
+    move |input: I| recognize(many0_count(terminated(opt(crlf_with(policy)), wsp)))(input)
+}
+
+/// LWSP without whitespace-only lines
+///
+/// LWSP = *WSP [CRLF 1*WSP] ; not followed by CRLF or the end of input
+///
+/// A second fold, or a fold whose white space is followed by CRLF or the end of input,
+/// would leave a line which only contains white space, so LWSP stops before it.
+/// Whether the line preceding the first fold is whitespace-only depends on the input
+/// before LWSP and is not checked.
+pub fn lwsp_no_empty_lines<I, E>(input: I) -> IResult<I, I, E>
+where
+    I: Clone
+        + Offset
+        + PartialEq
+        + InputIter
+        + InputLength
+        + Slice<RangeTo<usize>>
+        + Slice<RangeFrom<usize>>,
+    <I as InputIter>::Item: AsChar,
+    E: ParseError<I>,
+{
+    lwsp_no_empty_lines_with(NewlinePolicy::Strict)(input)
+}
+
+/// LWSP without whitespace-only lines and with line endings according to `policy`.
+///
+/// See [`lwsp_no_empty_lines`].
+pub fn lwsp_no_empty_lines_with<I, E>(policy: NewlinePolicy) -> impl Fn(I) -> IResult<I, I, E>
+where
+    I: Clone
+        + Offset
+        + PartialEq
+        + InputIter
+        + InputLength
+        + Slice<RangeTo<usize>>
+        + Slice<RangeFrom<usize>>,
+    <I as InputIter>::Item: AsChar,
+    E: ParseError<I>,
+{
+    move |input: I| {
+        let line_end = alt((crlf_with(policy), eof));
+
+        recognize(pair(
+            many0_count(wsp),
+            opt(terminated(
+                pair(crlf_with(policy), many1_count(wsp)),
+                not(line_end),
+            )),
+        ))(input)
+    }
+}
+
+/// LWSP which also reports its folds.
+///
+/// See [`Lwsp`] and [`Fold`].
+#[cfg(feature = "alloc")]
+pub fn lwsp_detailed<I, E>(input: I) -> IResult<I, Lwsp<I>, E>
+where
+    I: Clone + Offset + InputIter + InputLength + Slice<RangeTo<usize>> + Slice<RangeFrom<usize>>,
+    <I as InputIter>::Item: AsChar,
+    E: ParseError<I>,
+{
+    lwsp_detailed_with(NewlinePolicy::Strict)(input)
+}
+
+/// LWSP which also reports its folds, with line endings according to `policy`.
+///
+/// See [`lwsp_detailed`].
+#[cfg(feature = "alloc")]
+pub fn lwsp_detailed_with<I, E>(policy: NewlinePolicy) -> impl Fn(I) -> IResult<I, Lwsp<I>, E>
+where
+    I: Clone + Offset + InputIter + InputLength + Slice<RangeTo<usize>> + Slice<RangeFrom<usize>>,
+    <I as InputIter>::Item: AsChar,
+    E: ParseError<I>,
+{
+    move |input: I| {
+        let mut folds: Vec<Fold> = Vec::new();
+        let mut rest = input.clone();
+
+        loop {
+            match pair(opt(crlf_with(policy)), wsp)(rest.clone()) {
+                Ok((remaining, (newline, _))) => {
+                    if newline.is_some() {
+                        if let Some(previous) = folds.last_mut() {
+                            previous.whitespace_only = true;
+                        }
+                        folds.push(Fold {
+                            offset: input.offset(&rest),
+                            whitespace_only: false,
+                        });
+                    }
+                    rest = remaining;
+                }
+                Err(OutCome::Error(_)) => break,
+                Err(error) => return Err(error),
+            }
+        }
+
+        // The last continuation line is whitespace-only, too, if a line ending or the end
+        // of input follows.
+        if rest.input_len() == 0 || crlf_with::<_, E>(policy)(rest.clone()).is_ok() {
+            if let Some(last) = folds.last_mut() {
+                last.whitespace_only = true;
+            }
+        }
+
+        let span = input.slice(..input.offset(&rest));
+
+        Ok((rest, Lwsp { span, folds }))
+    }
+}
+
 /// OCTET = %x00-FF ; 8 bits of data
-pub fn octet<E>(input: &[u8]) -> IResult<&[u8], u8, E>
+pub fn octet<I, E>(input: I) -> IResult<I, u8, E>
 where
-    for<'a> E: ParseError<&'a [u8]>,
+    I: InputIter<Item = u8> + Slice<RangeFrom<usize>>,
+    E: ParseError<I>,
 {
-    match input.split_first() {
+    match input.iter_elements().next() {
         None => Err(OutCome::Incomplete(Needed::new(1))),
-        Some((&b, tail)) => Ok((tail, b)),
+        Some(b) => Ok((input.slice(1..), b)),
     }
 }
 
+/// n*n OCTET ; exactly `n` bytes of data
+pub fn octets<I, E>(n: usize) -> impl Fn(I) -> IResult<I, I, E>
+where
+    I: InputIter<Item = u8> + InputTake + InputLength,
+    E: ParseError<I>,
+{
+    take(n)
+}
+
 /// SP = %x20
 pub fn sp<I, E>(input: I) -> IResult<I, char, E>
 where
@@ -200,7 +364,7 @@ where
     <I as InputIter>::Item: AsChar,
     E: ParseError<I>,
 {
-    satisfy(is_char)(input)
+    satisfy(is_vchar)(input)
 }
 
 /// White space
@@ -215,8 +379,54 @@ where
     satisfy(is_wsp)(input)
 }
 
+/// Core rules in their strict reading
+///
+/// HEXDIG is uppercase only, LWSP does not span whitespace-only lines, and line endings
+/// are CRLF only.
+pub mod strict {
+    use core::ops::RangeFrom;
+
+    use nom::{
+        character::streaming::satisfy, error::ParseError, AsChar, IResult, InputIter, InputLength,
+        Slice,
+    };
+
+    pub use super::{
+        alpha, bit, char, cr, crlf, ctl, digit, dquote, htab, lf, lwsp_no_empty_lines as lwsp,
+        octet, octets, sp, vchar, wsp,
+    };
+    use crate::is_hexdig_strict;
+
+    /// HEXDIG = DIGIT / %x41-46
+    ///
+    /// Note: only uppercase `A` / ... / `F` are allowed.
+    pub fn hexdig<I, E>(input: I) -> IResult<I, char, E>
+    where
+        I: InputLength + InputIter + Slice<RangeFrom<usize>> + Clone,
+        <I as InputIter>::Item: AsChar,
+        E: ParseError<I>,
+    {
+        satisfy(is_hexdig_strict)(input)
+    }
+}
+
+/// Core rules in their lenient reading
+///
+/// HEXDIG is case-insensitive and LWSP is implemented as written in RFC5234.
+pub mod lenient {
+    pub use super::{
+        alpha, bit, char, cr, crlf, crlf_relaxed, crlf_with, ctl, digit, dquote, hexdig, htab, lf,
+        lwsp, lwsp_with, octet, octets, sp, vchar, wsp,
+    };
+}
+
 #[cfg(test)]
 mod tests {
+    use nom::error::Error;
+    // `VerboseError` needs `alloc`. The tests only check for failure, so `Error` will do.
+    #[cfg(not(feature = "alloc"))]
+    use nom::error::Error as VerboseError;
+    #[cfg(feature = "alloc")]
     use nom::error::VerboseError;
 
     use super::*;
@@ -229,4 +439,125 @@ mod tests {
         assert!(cr::<_, VerboseError<_>>(&b"\n"[..]).is_err());
         assert_eq!(cr::<_, VerboseError<_>>(&b"\r"[..]), Ok((&b""[..], '\r')));
     }
+
+    #[test]
+    fn test_octets() {
+        assert_eq!(
+            octet::<_, Error<&[u8]>>(b""),
+            Err(OutCome::Incomplete(Needed::new(1)))
+        );
+        assert_eq!(
+            octets::<_, Error<&[u8]>>(3)(b"a"),
+            Err(OutCome::Incomplete(Needed::new(2)))
+        );
+
+        assert_eq!(octet::<_, Error<&[u8]>>(b"\xff"), Ok((&b""[..], 0xff)));
+        assert_eq!(
+            octets::<_, Error<&[u8]>>(2)(b"abc"),
+            Ok((&b"c"[..], &b"ab"[..]))
+        );
+    }
+
+    #[test]
+    fn test_crlf_with() {
+        assert!(matches!(
+            crlf_with::<_, Error<&str>>(NewlinePolicy::Strict)("\r"),
+            Err(OutCome::Incomplete(_))
+        ));
+        // A trailing CR may still be followed by LF.
+        assert!(matches!(
+            crlf_with::<_, Error<&str>>(NewlinePolicy::AllowCr)("\r"),
+            Err(OutCome::Incomplete(_))
+        ));
+        assert_eq!(
+            crlf_with::<_, Error<&str>>(NewlinePolicy::Any)("\rx"),
+            Ok(("x", "\r"))
+        );
+        assert_eq!(
+            lwsp_with::<_, Error<&str>>(NewlinePolicy::AllowLf)(" \n x"),
+            Ok(("x", " \n "))
+        );
+    }
+
+    #[test]
+    fn test_strict() {
+        assert!(strict::hexdig::<_, Error<&str>>("a").is_err());
+        assert_eq!(strict::hexdig::<_, Error<&str>>("A"), Ok(("", 'A')));
+
+        assert_eq!(
+            strict::lwsp::<_, Error<&str>>(" \r\n \r\n x"),
+            Ok(("\r\n \r\n x", " "))
+        );
+        assert!(matches!(
+            strict::lwsp::<_, Error<&str>>(" \r\n"),
+            Err(OutCome::Incomplete(_))
+        ));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_lwsp_detailed() {
+        assert!(matches!(
+            lwsp_detailed::<_, Error<&str>>(" \r\n "),
+            Err(OutCome::Incomplete(_))
+        ));
+
+        let (rem, lwsp) = lwsp_detailed::<_, Error<&str>>(" \r\n \r\n x").unwrap();
+        assert_eq!(rem, "x");
+        assert_eq!(lwsp.span, " \r\n \r\n ");
+        assert!(lwsp.folds[0].whitespace_only);
+        assert!(!lwsp.folds[1].whitespace_only);
+
+        let (rem, lwsp) = lwsp_detailed::<_, Error<&str>>(" \r\n \r\nX").unwrap();
+        assert_eq!(rem, "\r\nX");
+        assert_eq!(lwsp.span, " \r\n ");
+        assert!(lwsp.folds[0].whitespace_only);
+    }
+
+    #[test]
+    fn test_lwsp_no_empty_lines() {
+        assert_eq!(
+            lwsp_no_empty_lines::<_, Error<&str>>(" \r\n\tx"),
+            Ok(("x", " \r\n\t"))
+        );
+        assert_eq!(
+            lwsp_no_empty_lines::<_, Error<&str>>(" \r\n \r\nX"),
+            Ok(("\r\n \r\nX", " "))
+        );
+    }
+
+    #[test]
+    fn test_lwsp_no_empty_lines_with() {
+        assert_eq!(
+            lwsp_no_empty_lines_with::<_, Error<&str>>(NewlinePolicy::AllowLf)(" \n\tx"),
+            Ok(("x", " \n\t"))
+        );
+        assert_eq!(
+            lwsp_no_empty_lines_with::<_, Error<&str>>(NewlinePolicy::AllowLf)(" \n \nX"),
+            Ok(("\n \nX", " "))
+        );
+        assert_eq!(
+            lwsp_no_empty_lines_with::<_, Error<&str>>(NewlinePolicy::AllowCr)(" \r\tx"),
+            Ok(("x", " \r\t"))
+        );
+        assert_eq!(
+            lwsp_no_empty_lines_with::<_, Error<&str>>(NewlinePolicy::AllowCr)(" \r \rX"),
+            Ok(("\r \rX", " "))
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_lwsp_detailed_with() {
+        let (rem, lwsp) =
+            lwsp_detailed_with::<_, Error<&str>>(NewlinePolicy::AllowLf)(" \n \n x").unwrap();
+        assert_eq!(rem, "x");
+        assert!(lwsp.folds[0].whitespace_only);
+        assert!(!lwsp.folds[1].whitespace_only);
+
+        let (rem, lwsp) =
+            lwsp_detailed_with::<_, Error<&str>>(NewlinePolicy::AllowCr)(" \r \rX").unwrap();
+        assert_eq!(rem, "\rX");
+        assert!(lwsp.folds[0].whitespace_only);
+    }
 }
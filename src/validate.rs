@@ -0,0 +1,279 @@
+//! Whole-input validation against core rules
+//!
+//! ```
+//! use abnf_core::validate::{validate_as, OneOrMore, ValidationError, Vchar};
+//!
+//! assert_eq!(validate_as::<OneOrMore<Vchar>>(b"token"), Ok(()));
+//! assert_eq!(
+//!     validate_as::<OneOrMore<Vchar>>(b"to ken"),
+//!     Err(ValidationError { offset: 2 })
+//! );
+//! ```
+
+use core::fmt;
+
+use nom::{error::Error, Err as OutCome, IResult};
+
+use crate::complete;
+
+/// Input does not match a rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Offset of the first byte which does not match
+    pub offset: usize,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid input at offset {}", self.offset)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+/// A rule which can be matched against the start of an input.
+pub trait Rule {
+    /// Length of the match at the start of `input`, or the offset at which matching failed.
+    fn match_len(input: &[u8]) -> Result<usize, usize>;
+}
+
+/// Check that a whole input matches a rule.
+pub trait Validate {
+    fn validate(input: &[u8]) -> Result<(), ValidationError>;
+}
+
+impl<R: Rule> Validate for R {
+    fn validate(input: &[u8]) -> Result<(), ValidationError> {
+        match R::match_len(input) {
+            Ok(len) if len == input.len() => Ok(()),
+            Ok(offset) | Err(offset) => Err(ValidationError { offset }),
+        }
+    }
+}
+
+/// Check that `input` matches `R` as a whole.
+pub fn validate_as<R: Validate>(input: &[u8]) -> Result<(), ValidationError> {
+    R::validate(input)
+}
+
+fn match_len<'a, O>(
+    input: &'a [u8],
+    parser: impl FnOnce(&'a [u8]) -> IResult<&'a [u8], O, Error<&'a [u8]>>,
+) -> Result<usize, usize> {
+    match parser(input) {
+        Ok((rest, _)) => Ok(input.len() - rest.len()),
+        Err(OutCome::Error(error) | OutCome::Failure(error)) => {
+            Err(input.len() - error.input.len())
+        }
+        Err(OutCome::Incomplete(_)) => Err(input.len()),
+    }
+}
+
+macro_rules! rule {
+    ($(#[$meta:meta])* $name:ident, $parser:path) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug)]
+        pub struct $name;
+
+        impl Rule for $name {
+            fn match_len(input: &[u8]) -> Result<usize, usize> {
+                match_len(input, $parser)
+            }
+        }
+    };
+}
+
+rule!(
+    /// ALPHA = %x41-5A / %x61-7A
+    Alpha,
+    complete::alpha
+);
+rule!(
+    /// BIT = "0" / "1"
+    Bit,
+    complete::bit
+);
+rule!(
+    /// CHAR = %x01-7F
+    Char,
+    complete::char
+);
+rule!(
+    /// CR = %x0D
+    Cr,
+    complete::cr
+);
+rule!(
+    /// CRLF = CR LF
+    Crlf,
+    complete::crlf
+);
+rule!(
+    /// CTL = %x00-1F / %x7F
+    Ctl,
+    complete::ctl
+);
+rule!(
+    /// DIGIT = %x30-39
+    Digit,
+    complete::digit
+);
+rule!(
+    /// DQUOTE = %x22
+    Dquote,
+    complete::dquote
+);
+rule!(
+    /// HEXDIG = DIGIT / "A" / "B" / "C" / "D" / "E" / "F"
+    Hexdig,
+    complete::hexdig
+);
+rule!(
+    /// HTAB = %x09
+    Htab,
+    complete::htab
+);
+rule!(
+    /// LF = %x0A
+    Lf,
+    complete::lf
+);
+rule!(
+    /// LWSP = *(WSP / CRLF WSP)
+    Lwsp,
+    complete::lwsp
+);
+rule!(
+    /// OCTET = %x00-FF
+    Octet,
+    complete::octet
+);
+rule!(
+    /// SP = %x20
+    Sp,
+    complete::sp
+);
+rule!(
+    /// VCHAR = %x21-7E
+    Vchar,
+    complete::vchar
+);
+rule!(
+    /// WSP = SP / HTAB
+    Wsp,
+    complete::wsp
+);
+
+/// MIN*MAX R
+///
+/// A failing repetition after MIN repetitions ends the match, unless it matched a part
+/// of the input before it failed. Then its failure offset is reported.
+///
+/// MIN must not be greater than MAX, which is checked at compile time.
+///
+/// ```compile_fail
+/// use abnf_core::validate::{validate_as, Digit, Repeat};
+///
+/// validate_as::<Repeat<Digit, 3, 1>>(b"12");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Repeat<R, const MIN: usize, const MAX: usize>(core::marker::PhantomData<R>);
+
+impl<R, const MIN: usize, const MAX: usize> Repeat<R, MIN, MAX> {
+    const MIN_LE_MAX: () = assert!(MIN <= MAX, "MIN must not be greater than MAX");
+}
+
+/// *R
+pub type ZeroOrMore<R> = Repeat<R, 0, { usize::MAX }>;
+
+/// 1*R
+pub type OneOrMore<R> = Repeat<R, 1, { usize::MAX }>;
+
+impl<R: Rule, const MIN: usize, const MAX: usize> Rule for Repeat<R, MIN, MAX> {
+    fn match_len(input: &[u8]) -> Result<usize, usize> {
+        let () = Self::MIN_LE_MAX;
+
+        let mut len = 0;
+        let mut count = 0;
+
+        while count < MAX {
+            match R::match_len(&input[len..]) {
+                // An empty match would repeat forever.
+                Ok(0) => break,
+                Ok(n) => {
+                    len += n;
+                    count += 1;
+                }
+                // A partially matched repetition points at the offending byte,
+                // not at the start of the repetition.
+                Err(offset) if count < MIN || offset > 0 => return Err(len + offset),
+                Err(_) => break,
+            }
+        }
+
+        if count < MIN {
+            return Err(len);
+        }
+
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single() {
+        assert_eq!(validate_as::<Alpha>(b"a"), Ok(()));
+        assert_eq!(
+            validate_as::<Alpha>(b""),
+            Err(ValidationError { offset: 0 })
+        );
+        assert_eq!(
+            validate_as::<Alpha>(b"ab"),
+            Err(ValidationError { offset: 1 })
+        );
+        assert_eq!(validate_as::<Crlf>(b"\r\n"), Ok(()));
+        assert_eq!(
+            validate_as::<Crlf>(b"\rx"),
+            Err(ValidationError { offset: 1 })
+        );
+        assert_eq!(validate_as::<Lwsp>(b""), Ok(()));
+        assert_eq!(validate_as::<Lwsp>(b" \r\n\t"), Ok(()));
+        assert_eq!(
+            validate_as::<Lwsp>(b" \r\n"),
+            Err(ValidationError { offset: 1 })
+        );
+        assert_eq!(validate_as::<Octet>(b"\xff"), Ok(()));
+    }
+
+    #[test]
+    fn test_repeat() {
+        assert_eq!(validate_as::<ZeroOrMore<Vchar>>(b""), Ok(()));
+        assert_eq!(
+            validate_as::<OneOrMore<Vchar>>(b""),
+            Err(ValidationError { offset: 0 })
+        );
+        assert_eq!(validate_as::<OneOrMore<Vchar>>(b"abc~"), Ok(()));
+        assert_eq!(
+            validate_as::<OneOrMore<Vchar>>(b"ab\x7f"),
+            Err(ValidationError { offset: 2 })
+        );
+        assert_eq!(
+            validate_as::<Repeat<Digit, 2, 3>>(b"1"),
+            Err(ValidationError { offset: 1 })
+        );
+        assert_eq!(validate_as::<Repeat<Digit, 2, 3>>(b"123"), Ok(()));
+        assert_eq!(
+            validate_as::<Repeat<Digit, 2, 3>>(b"1234"),
+            Err(ValidationError { offset: 3 })
+        );
+        assert_eq!(
+            validate_as::<OneOrMore<Crlf>>(b"\r\n\r\n\rx"),
+            Err(ValidationError { offset: 5 })
+        );
+        assert_eq!(validate_as::<ZeroOrMore<Lwsp>>(b" \t"), Ok(()));
+    }
+}
@@ -0,0 +1,386 @@
+//! ABNF Core Rules (RFC5234 B.1.) for winnow
+//!
+//! winnow decides between complete and streaming parsing by the stream type, so
+//! every rule is provided once and works on `&str`, `&[u8]` and their
+//! [`Partial`](winnow::Partial) counterparts. Failing rules are labeled with
+//! their name, e.g. `StrContext::Label("ALPHA")`.
+
+use winnow::{
+    combinator::{alt, opt, repeat},
+    error::{AddContext, ParserError, StrContext},
+    stream::{AsChar, Stream, StreamIsPartial},
+    token::{any, take},
+    Parser,
+};
+
+use crate::{
+    is_alpha, is_bit, is_char, is_cr, is_ctl, is_digit, is_dquote, is_hexdig, is_htab, is_lf,
+    is_sp, is_vchar, is_wsp, NewlinePolicy,
+};
+
+fn satisfy<I, E>(predicate: fn(char) -> bool, label: &'static str) -> impl Parser<I, char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    any.verify_map(move |token: <I as Stream>::Token| {
+        let c = token.as_char();
+        predicate(c).then_some(c)
+    })
+    .context(StrContext::Label(label))
+}
+
+/// ALPHA = %x41-5A / %x61-7A ; A-Z / a-z
+pub fn alpha<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_alpha, "ALPHA").parse_next(input)
+}
+
+/// BIT = "0" / "1"
+pub fn bit<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_bit, "BIT").parse_next(input)
+}
+
+/// CHAR = %x01-7F ; any 7-bit US-ASCII character, excluding NUL
+pub fn char<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_char, "CHAR").parse_next(input)
+}
+
+/// Carriage return
+///
+/// CR = %x0D
+pub fn cr<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_cr, "CR").parse_next(input)
+}
+
+/// Internet standard newline
+///
+/// CRLF = CR LF
+///
+/// Note: this variant will strictly expect "\r\n".
+/// Use [crlf_relaxed](fn.crlf_relaxed.html) to accept "\r\n" as well as only "\n".
+pub fn crlf<I, E>(input: &mut I) -> Result<(char, char), E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    (cr, lf)
+        .context(StrContext::Label("CRLF"))
+        .parse_next(input)
+}
+
+/// Newline, with and without "\r".
+pub fn crlf_relaxed<I, E>(input: &mut I) -> Result<(Option<char>, char), E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    (opt(cr), lf)
+        .context(StrContext::Label("CRLF"))
+        .parse_next(input)
+}
+
+/// Newline according to `policy`.
+///
+/// Returns the recognized line ending.
+pub fn crlf_with<I, E>(policy: NewlinePolicy) -> impl Parser<I, <I as Stream>::Slice, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    let newline = move |input: &mut I| match policy {
+        NewlinePolicy::Strict => (cr, lf).take().parse_next(input),
+        NewlinePolicy::AllowLf => (opt(cr), lf).take().parse_next(input),
+        NewlinePolicy::AllowCr => (cr, opt(lf)).take().parse_next(input),
+        NewlinePolicy::Any => alt(((cr, opt(lf)).take(), lf.take())).parse_next(input),
+    };
+
+    newline.context(StrContext::Label("CRLF"))
+}
+
+/// CTL = %x00-1F / %x7F ; controls
+pub fn ctl<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_ctl, "CTL").parse_next(input)
+}
+
+/// DIGIT = %x30-39 ; 0-9
+pub fn digit<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_digit, "DIGIT").parse_next(input)
+}
+
+/// Double Quote
+///
+/// DQUOTE = %x22
+pub fn dquote<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_dquote, "DQUOTE").parse_next(input)
+}
+
+/// HEXDIG = DIGIT / "A" / "B" / "C" / "D" / "E" / "F"
+pub fn hexdig<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_hexdig, "HEXDIG").parse_next(input)
+}
+
+/// Horizontal tab
+///
+/// HTAB = %x09
+pub fn htab<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_htab, "HTAB").parse_next(input)
+}
+
+/// Linefeed
+///
+/// LF = %x0A
+pub fn lf<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_lf, "LF").parse_next(input)
+}
+
+/// Use of this linear-white-space rule permits lines containing only white
+/// space that are no longer legal in mail headers and have caused
+/// interoperability problems in other contexts.
+///
+/// Do not use when defining mail headers and use with caution in other contexts.
+///
+/// LWSP = *(WSP / CRLF WSP)
+pub fn lwsp<I, E>(input: &mut I) -> Result<<I as Stream>::Slice, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    // code as equivalent avoid branching LWSP = *([CRLF] WSP)
+    repeat::<_, _, (), _, _>(0.., (opt(crlf), wsp))
+        .take()
+        .context(StrContext::Label("LWSP"))
+        .parse_next(input)
+}
+
+/// LWSP with line endings according to `policy`.
+///
+/// LWSP = *(WSP / newline WSP)
+///
+/// See [lwsp](fn.lwsp.html) for why this rule should be used with caution.
+pub fn lwsp_with<I, E>(policy: NewlinePolicy) -> impl Parser<I, <I as Stream>::Slice, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    repeat::<_, _, (), _, _>(0.., (opt(crlf_with(policy)), wsp))
+        .take()
+        .context(StrContext::Label("LWSP"))
+}
+
+/// OCTET = %x00-FF ; 8 bits of data
+pub fn octet<I, E>(input: &mut I) -> Result<u8, E>
+where
+    I: StreamIsPartial + Stream<Token = u8>,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    any.context(StrContext::Label("OCTET")).parse_next(input)
+}
+
+/// n*n OCTET ; exactly `n` bytes of data
+pub fn octets<I, E>(n: usize) -> impl Parser<I, <I as Stream>::Slice, E>
+where
+    I: StreamIsPartial + Stream<Token = u8>,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    take(n).context(StrContext::Label("OCTET"))
+}
+
+/// SP = %x20
+pub fn sp<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_sp, "SP").parse_next(input)
+}
+
+/// VCHAR = %x21-7E ; visible (printing) characters
+pub fn vchar<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_vchar, "VCHAR").parse_next(input)
+}
+
+/// White space
+///
+/// WSP = SP / HTAB
+pub fn wsp<I, E>(input: &mut I) -> Result<char, E>
+where
+    I: StreamIsPartial + Stream,
+    <I as Stream>::Token: AsChar,
+    E: ParserError<I> + AddContext<I, StrContext>,
+{
+    satisfy(is_wsp, "WSP").parse_next(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use winnow::{
+        error::{ContextError, ErrMode},
+        Partial,
+    };
+
+    use super::*;
+
+    type Error = ErrMode<ContextError>;
+
+    #[test]
+    fn test_alpha() {
+        assert!(alpha::<_, Error>.parse_peek("").is_err());
+        assert!(alpha::<_, Error>.parse_peek("`").is_err());
+        assert_eq!(alpha::<_, Error>.parse_peek("a"), Ok(("", 'a')));
+        assert_eq!(alpha::<_, Error>.parse_peek("Z"), Ok(("", 'Z')));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_context() {
+        let err = alpha::<_, Error>.parse_peek("0").unwrap_err();
+        let ErrMode::Backtrack(err) = err else {
+            panic!("unexpected {err:?}");
+        };
+        assert!(err.context().eq([&StrContext::Label("ALPHA")]));
+    }
+
+    #[test]
+    fn test_crlf() {
+        assert!(crlf::<_, Error>.parse_peek("\n").is_err());
+        assert_eq!(
+            crlf::<_, Error>.parse_peek("\r\nx"),
+            Ok(("x", ('\r', '\n')))
+        );
+
+        assert!(crlf::<_, Error>
+            .parse_peek(Partial::new(&b"\r"[..]))
+            .unwrap_err()
+            .is_incomplete());
+    }
+
+    #[test]
+    fn test_crlf_relaxed() {
+        assert_eq!(
+            crlf_relaxed::<_, Error>.parse_peek("\n"),
+            Ok(("", (None, '\n')))
+        );
+        assert_eq!(
+            crlf_relaxed::<_, Error>.parse_peek("\r\n"),
+            Ok(("", (Some('\r'), '\n')))
+        );
+    }
+
+    #[test]
+    fn test_crlf_with() {
+        assert!(crlf_with::<_, Error>(NewlinePolicy::Strict)
+            .parse_peek("\n")
+            .is_err());
+        assert_eq!(
+            crlf_with::<_, Error>(NewlinePolicy::AllowLf).parse_peek("\nx"),
+            Ok(("x", "\n"))
+        );
+        assert_eq!(
+            crlf_with::<_, Error>(NewlinePolicy::AllowCr).parse_peek("\rx"),
+            Ok(("x", "\r"))
+        );
+        assert_eq!(
+            crlf_with::<_, Error>(NewlinePolicy::Any).parse_peek("\r\nx"),
+            Ok(("x", "\r\n"))
+        );
+        assert_eq!(
+            lwsp_with::<_, Error>(NewlinePolicy::Any).parse_peek(" \r \n x"),
+            Ok(("x", " \r \n "))
+        );
+    }
+
+    #[test]
+    fn test_lwsp() {
+        assert_eq!(
+            lwsp::<_, Error>.parse_peek(" \r\n\tx"),
+            Ok(("x", " \r\n\t"))
+        );
+        assert_eq!(lwsp::<_, Error>.parse_peek(" \r\nx"), Ok(("\r\nx", " ")));
+
+        assert!(lwsp::<_, Error>
+            .parse_peek(Partial::new(&b" \r\n"[..]))
+            .unwrap_err()
+            .is_incomplete());
+    }
+
+    #[test]
+    fn test_octet() {
+        assert!(octet::<_, Error>.parse_peek(&b""[..]).is_err());
+        assert_eq!(
+            octet::<_, Error>.parse_peek(&b"\xff"[..]),
+            Ok((&b""[..], 0xff))
+        );
+        assert!(octet::<_, Error>
+            .parse_peek(Partial::new(&b""[..]))
+            .unwrap_err()
+            .is_incomplete());
+
+        assert!(octets::<_, Error>(2).parse_peek(&b"a"[..]).is_err());
+        assert_eq!(
+            octets::<_, Error>(2).parse_peek(&b"abc"[..]),
+            Ok((&b"c"[..], &b"ab"[..]))
+        );
+    }
+}